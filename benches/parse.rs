@@ -0,0 +1,144 @@
+//! Parsing throughput on generated prefetch files
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use tap_plugin_prefetch::{Prefetch, PrefetchParseOptions, scan_prefetch_folder};
+use tap_plugin_prefetch::mam::MamReader;
+
+/// FILETIME of 2020-09-13T12:26:40Z
+const FILETIME : u64 = 132_444_736_000_000_000;
+
+fn put_u32(buffer : &mut Vec<u8>, offset : usize, value : u32)
+{
+  buffer[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn utf16(text : &str) -> Vec<u8>
+{
+  text.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect()
+}
+
+/// Build a prefetch referencing `file_count` files on one volume, entries are 0x28 bytes on XP, 0x60 on Windows 10
+/// and 0x68 otherwise
+fn build(version : u32, execution_time_offset : usize, run_count_offset : usize, volume_entry_size : usize, file_count : usize) -> Vec<u8>
+{
+  let mut buffer = vec![0u8; 0x100];
+
+  buffer[0x0..0x4].copy_from_slice(&version.to_le_bytes());
+  buffer[0x4..0x8].copy_from_slice(b"SCCA");
+  let name = utf16("BENCH.EXE");
+  buffer[0x10..0x10 + name.len()].copy_from_slice(&name);
+
+  let paths : Vec<u8> = (0..file_count).flat_map(|index| utf16(&format!("\\VOLUME{{01d0000000000000-12345678}}\\WINDOWS\\SYSTEM32\\LIBRARY{}.DLL\0", index))).collect();
+  put_u32(&mut buffer, 0x64, 0x100);
+  put_u32(&mut buffer, 0x68, paths.len() as u32);
+  buffer.extend(paths);
+
+  let volume_offset = buffer.len();
+  put_u32(&mut buffer, 0x6c, volume_offset as u32);
+  put_u32(&mut buffer, 0x70, 1);
+  put_u32(&mut buffer, 0x74, volume_entry_size as u32);
+  buffer.resize(volume_offset + volume_entry_size, 0);
+  buffer[volume_offset + 8..volume_offset + 16].copy_from_slice(&FILETIME.to_le_bytes());
+
+  buffer[execution_time_offset..execution_time_offset + 8].copy_from_slice(&FILETIME.to_le_bytes());
+  put_u32(&mut buffer, run_count_offset, 42);
+  let size = buffer.len() as u32;
+  put_u32(&mut buffer, 0xc, size);
+  buffer
+}
+
+/// Store `data` in a MAM container using only Huffman literals
+fn compress(data : &[u8]) -> Vec<u8>
+{
+  let mut compressed = b"MAM\x04".to_vec();
+  compressed.extend((data.len() as u32).to_le_bytes());
+
+  for block in data.chunks(65536)
+  {
+    compressed.extend([0x88u8; 128]);
+    compressed.extend([0u8; 128]);
+    for pair in block.chunks(2)
+    {
+      compressed.push(pair.get(1).copied().unwrap_or(0));
+      compressed.push(pair[0]);
+    }
+    compressed.extend([0u8; 2]);
+  }
+  compressed
+}
+
+fn parse(c : &mut Criterion)
+{
+  let fixtures = [("xp_small", build(0x11, 0x78, 0x90, 0x28, 16)), ("windows8_large", build(0x1a, 0x80, 0xd0, 0x68, 4096)),
+                  ("windows10_large", build(0x1e, 0x80, 0xd0, 0x60, 4096))];
+
+  let mut group = c.benchmark_group("parse");
+  for (name, data) in fixtures.iter()
+  {
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function(*name, |b| b.iter(|| Prefetch::from_bytes(data).unwrap()));
+  }
+
+  let (_, data) = &fixtures[1];
+  let options = PrefetchParseOptions{ skip_file_list : true, ..Default::default() };
+  group.throughput(Throughput::Bytes(data.len() as u64));
+  group.bench_function("windows8_large_skip_file_list", |b| b.iter(||
+    Prefetch::from_file_with_options(&mut Cursor::new(data.as_slice()), &options).unwrap()));
+  group.finish();
+}
+
+fn decompress(c : &mut Criterion)
+{
+  let data = build(0x1e, 0x80, 0xd0, 0x60, 4096);
+  let compressed = compress(&data);
+
+  let mut group = c.benchmark_group("decompress");
+  group.throughput(Throughput::Bytes(data.len() as u64));
+  group.bench_function("windows10_large", |b| b.iter(||
+  {
+    let mut reader = MamReader::new(Cursor::new(compressed.as_slice())).unwrap();
+    let mut output = Vec::new();
+    reader.read_to_end(&mut output).unwrap();
+    output
+  }));
+  group.finish();
+}
+
+fn directory(c : &mut Criterion)
+{
+  let directory = std::env::temp_dir().join("tap-plugin-prefetch-bench");
+  fs::create_dir_all(&directory).unwrap();
+  for index in 0..100
+  {
+    fs::write(directory.join(format!("BENCH{}.EXE-00000000.pf", index)), build(0x17, 0x80, 0x98, 0x68, 64)).unwrap();
+  }
+
+  c.bench_function("directory", |b| b.iter(|| scan_prefetch_folder(&directory).unwrap()));
+
+  fs::remove_dir_all(&directory).unwrap();
+}
+
+/// Parse from a file with different read buffer sizes, the parser seek a lot so small buffers do many reads
+fn buffer(c : &mut Criterion)
+{
+  let path = std::env::temp_dir().join("tap-plugin-prefetch-bench-buffer.pf");
+  let data = build(0x1a, 0x80, 0xd0, 0x68, 4096);
+  fs::write(&path, &data).unwrap();
+
+  let mut group = c.benchmark_group("buffer");
+  group.throughput(Throughput::Bytes(data.len() as u64));
+  for size in [8 * 1024, 64 * 1024, 256 * 1024]
+  {
+    group.bench_function(format!("{}k", size / 1024), |b| b.iter(||
+      Prefetch::from_file(&mut BufReader::with_capacity(size, File::open(&path).unwrap())).unwrap()));
+  }
+  group.finish();
+
+  fs::remove_file(&path).unwrap();
+}
+
+criterion_group!(benches, parse, decompress, directory, buffer);
+criterion_main!(benches);
@@ -2,38 +2,681 @@
 extern crate tap_plugin_prefetch;
 
 use std::env;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 use std::fs::File;
-use std::sync::Arc;
 use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
 
-use tap::value::Value;
-use tap_plugin_prefetch::Prefetch;
+use tap_plugin_prefetch::integrity::Confidence;
+use tap_plugin_prefetch::warning::Severity;
+use tap_plugin_prefetch::{Prefetch, PrefetchError, PrefetchFormat, PrefetchOutput, PrefetchParseOptions, PrefetchVersion,
+                          parse_prefetch_file_with_options, scan_prefetch_folder_with_options, scan_prefetch_tree_with_options,
+                          list_prefetch_folder, list_prefetch_tree};
 
-fn main() 
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+fn usage()
+{
+   println!("prefetch input_file");
+   println!("prefetch input_file_or_directory... (one json object per line)");
+   println!("prefetch --format csv input_file_or_directory... (one row per execution)");
+   println!("prefetch --schema (json schema of the single file output)");
+   println!("prefetch --schema batch (json schema of the json lines)");
+   println!("prefetch --version-only input_file...");
+   println!("prefetch --pecmd-csv input_file_or_directory...");
+   println!("prefetch --timeline input_file_or_directory...");
+   println!("prefetch --stats input_file_or_directory...");
+   println!("prefetch --group-by-volume input_file_or_directory...");
+   println!("prefetch --baseline baseline.json input_file_or_directory...");
+   println!("prefetch --errors-only input_file_or_directory...");
+   println!("prefetch --parquet output.parquet input_file_or_directory... (parquet feature)");
+   println!("batch modes accept --quiet to hide parsing warnings or --verbose to add them to the output");
+   println!("  (as a warnings column, the json lines always include them, --stats only count them");
+   println!("  and --group-by-volume print them on stderr)");
+   println!("--min-severity LEVEL only report the warnings of severity LEVEL (info, warning, error) or higher");
+   println!("--timeout MS abandon the parsing of a file after MS milliseconds, also with --threads");
+   println!("  (the worker of an abandoned file isn't killed, it keeps running until the end of the process)");
+   println!("--assume-version NAME parse with the layout of a version (WindowsXP, Windows7, Windows8, Windows10...)");
+   println!("--no-verify parse files whose signature isn't SCCA");
+   println!("--recursive search the directories (a mounted image root) for Prefetch folders");
+   println!("--threads N limit the number of threads parsing a directory (parallel feature)");
+}
+
+/// Reporting of the non fatal warnings of each file in batch mode
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Verbosity
+{
+   /// Only hard errors are reported
+   Quiet,
+   /// Warnings are printed on stderr
+   Normal,
+   /// Warnings are also included in the output
+   Verbose,
+}
+
+/// Options shared by the batch modes
+struct BatchOptions
 {
-   if env::args().len() != 2 
+   verbosity : Verbosity,
+   /// Warnings of a lower severity aren't reported
+   min_severity : Severity,
+   /// Maximum time spent parsing a single file
+   timeout : Option<Duration>,
+   parse : PrefetchParseOptions,
+   /// Search the directories for `Prefetch` folders
+   recursive : bool,
+}
+
+/// Remove every occurrence of `flag` from `args`, return true if there was one
+fn take_flag(args : &mut Vec<String>, flag : &str) -> bool
+{
+   let count = args.len();
+   args.retain(|arg| arg != flag);
+   args.len() != count
+}
+
+/// Remove `option` and its value from `args`, return the value
+fn take_option(args : &mut Vec<String>, option : &str) -> Option<String>
+{
+   let index = args.iter().position(|arg| arg == option)?;
+   args.remove(index);
+   if index < args.len()
    {
-     println!("prefetch input_file");
-     return ;
+      return Some(args.remove(index))
    }
+   None
+}
 
-   let args: Vec<String> = env::args().collect();
-   let file_path = &args[1];
+fn main()
+{
+   let mut args: Vec<String> = env::args().skip(1).collect();
+   let verbosity = match (take_flag(&mut args, "--quiet"), take_flag(&mut args, "--verbose"))
+   {
+      (true, _) => Verbosity::Quiet,
+      (false, true) => Verbosity::Verbose,
+      (false, false) => Verbosity::Normal,
+   };
+
+   let csv = match take_option(&mut args, "--format").as_deref()
+   {
+      None | Some("json") => false,
+      Some("csv") => true,
+      Some(format) => { eprintln!("Unknown format {}, expected json or csv", format); return },
+   };
+   let recursive = take_flag(&mut args, "--recursive");
+   let mut options = BatchOptions{ verbosity, min_severity : Severity::Info, timeout : None, parse : PrefetchParseOptions::default(), recursive };
+   if let Some(severity) = take_option(&mut args, "--min-severity")
+   {
+      match severity.parse::<Severity>()
+      {
+         Ok(severity) => options.min_severity = severity,
+         Err(err) => { eprintln!("{}", err); return },
+      }
+   }
+   options.parse.verify_signature = !take_flag(&mut args, "--no-verify");
+   if let Some(version) = take_option(&mut args, "--assume-version")
+   {
+      match version.parse::<PrefetchVersion>()
+      {
+         Ok(version) => options.parse.assume_version = Some(version),
+         Err(err) => { eprintln!("{}", err); return },
+      }
+   }
+   if let Some(timeout) = take_option(&mut args, "--timeout")
+   {
+      match timeout.parse::<u64>()
+      {
+         Ok(timeout) => options.timeout = Some(Duration::from_millis(timeout)),
+         Err(_) => { eprintln!("Invalid timeout {}", timeout); return },
+      }
+   }
 
+   if let Some(threads) = take_option(&mut args, "--threads")
+   {
+      match threads.parse::<usize>()
+      {
+         Ok(threads) => set_threads(threads),
+         Err(_) => { eprintln!("Invalid thread count {}", threads); return },
+      }
+   }
+
+   match args.first().map(|arg| arg.as_str())
+   {
+      Some("--schema") if args.get(1).map(|arg| arg.as_str()) == Some("batch") =>
+         println!("{}", serde_json::to_string_pretty(&schemars::schema_for!(BatchRecord)).unwrap()),
+      Some("--schema") => println!("{}", serde_json::to_string_pretty(&PrefetchOutput::schema()).unwrap()),
+      Some("--version-only") if args.len() > 1 =>
+      {
+         for file_path in &args[1..]
+         {
+            print_version(file_path);
+         }
+      },
+      Some("--pecmd-csv") if args.len() > 1 => print_pecmd_csv(&args[1..], &options),
+      Some("--timeline") if args.len() > 1 => print_timeline(&args[1..], &options),
+      Some("--stats") if args.len() > 1 => print_stats(&args[1..], &options),
+      Some("--group-by-volume") if args.len() > 1 => print_volume_groups(&args[1..], &options),
+      Some("--baseline") if args.len() > 2 => print_baseline(&args[1], &args[2..], &options),
+      Some("--errors-only") if args.len() > 1 => print_errors(&args[1..], &options),
+      Some("--parquet") if args.len() > 2 => write_parquet(&args[1], &args[2..], &options),
+      Some(file_path) if args.len() == 1 && !csv && !Path::new(file_path).is_dir() => print_json(file_path, &options.parse),
+      Some(path) if !path.starts_with("--") =>
+      {
+         let success = if csv { print_executions_csv(&args, &options) } else { print_jsonl(&args, &options) };
+         if !success
+         {
+            std::process::exit(1)
+         }
+      },
+      _ => usage(),
+   }
+}
+
+#[cfg(feature = "parallel")]
+fn set_threads(threads : usize)
+{
+   if let Err(err) = rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()
+   {
+      eprintln!("Can't set the number of threads: {}", err);
+   }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn set_threads(_threads : usize)
+{
+   eprintln!("--threads is ignored, built without the parallel feature");
+}
+
+/// Print the version of the file without parsing its body
+fn print_version(file_path : &str)
+{
    match File::open(file_path)
    {
       Err(_) => println!("Can't open file {}", file_path),
-      Ok(file) => 
+      Ok(file) =>
       {
          let mut buffered = BufReader::new(file);
-         let prefetch_parser = match Prefetch::from_file(&mut buffered)
+         match PrefetchFormat::from_reader(&mut buffered)
          {
-           Ok(prefetch_parser) => prefetch_parser,
-           Err(err) => {eprintln!("{}", err); return },
-         };
-      
-         let value : Value = Value::ReflectStruct(Arc::new(prefetch_parser));
-         println!("{}", serde_json::to_string(&value).unwrap());
+           Ok(format) => println!("{}: {}", file_path, format),
+           Err(err) => eprintln!("{}: {}", file_path, err),
+         }
       },
    }
 }
+
+/// Print the parsed file as json, the hash is also checked against the file name
+fn print_json(file_path : &str, options : &PrefetchParseOptions)
+{
+   match parse_prefetch_file_with_options(file_path, options)
+   {
+      Ok(prefetch) => println!("{}", serde_json::to_string(&prefetch.output()).unwrap()),
+      Err(err) => eprintln!("{}: {}", file_path, err),
+   }
+}
+
+/**
+ *   Line of the json lines batch output, the parsed prefetch with the file it was read from
+ */
+#[derive(Serialize, JsonSchema)]
+struct BatchRecord
+{
+   source : String,
+   prefetch : PrefetchOutput,
+}
+
+/// Print a json object with the source path for every input, return false if any input failed to parse
+fn print_jsonl(paths : &[String], options : &BatchOptions) -> bool
+{
+   let mut success = true;
+   for (path, result) in parse_inputs(paths, options)
+   {
+      match result
+      {
+         Ok(prefetch) =>
+         {
+            print_warnings(&path, &prefetch, options);
+            let record = BatchRecord{ source : path.display().to_string(), prefetch : prefetch.output() };
+            println!("{}", serde_json::to_string(&record).unwrap());
+         },
+         Err(err) => { eprintln!("{}: {}", path.display(), err); success = false },
+      }
+   }
+   success
+}
+
+/// Print a CSV row for each execution time of every input, return false if any input failed to parse
+fn print_executions_csv(paths : &[String], options : &BatchOptions) -> bool
+{
+   let mut success = true;
+   println!("source,executable,run_count,run_time,volume_serial,volume_created,volume_count{}", warnings_column(options));
+   for (path, result) in parse_inputs(paths, options)
+   {
+      match result
+      {
+         Ok(prefetch) =>
+         {
+            print_warnings(&path, &prefetch, options);
+            let volume = prefetch.volume();
+            let warnings = output_warnings(&prefetch, options);
+            for timestamp in prefetch.all_timestamps()
+            {
+               let mut row = vec![
+                  path.display().to_string(),
+                  prefetch.executable_name().to_string(),
+                  prefetch.run_count().to_string(),
+                  timestamp.to_rfc3339(),
+                  volume.map(|volume| volume.volume_serial_hex()).unwrap_or_default(),
+                  volume.and_then(|volume| volume.volume_creation_date()).map(|time| time.to_rfc3339()).unwrap_or_default(),
+                  prefetch.volume_count().to_string(),
+               ];
+               row.extend(warnings.clone());
+               print_csv_row(&row);
+            }
+         },
+         Err(err) => { eprintln!("{}: {}", path.display(), err); success = false },
+      }
+   }
+   success
+}
+
+/// Column names and order of PECmd CSV output, columns without an equivalent are left empty
+const PECMD_HEADER : &str = "Note,SourceFilename,SourceCreated,SourceModified,SourceAccessed,ExecutableName,Hash,Size,Version,RunCount,LastRun,\
+PreviousRun0,PreviousRun1,PreviousRun2,PreviousRun3,PreviousRun4,PreviousRun5,PreviousRun6,\
+Volume0Name,Volume0Serial,Volume0Created,Volume1Name,Volume1Serial,Volume1Created,Directories,FilesLoaded,ParsingError";
+
+/// Number of PreviousRun columns of PECmd
+const PECMD_PREVIOUS_RUNS : usize = 7;
+/// Number of volumes with their own columns in PECmd output
+const PECMD_VOLUMES : usize = 2;
+
+/// Parse every input file, directories are scanned for .pf files
+fn parse_inputs(paths : &[String], options : &BatchOptions) -> Vec<(PathBuf, anyhow::Result<Prefetch>)>
+{
+   let mut results = Vec::new();
+
+   for path in paths
+   {
+      if Path::new(path).is_dir()
+      {
+         let scanned = match (options.timeout, options.recursive)
+         {
+            (None, false) => scan_prefetch_folder_with_options(path, &options.parse),
+            (None, true) => scan_prefetch_tree_with_options(path, &options.parse),
+            (Some(timeout), recursive) =>
+            {
+               let files = if recursive { list_prefetch_tree(path) } else { list_prefetch_folder(path) };
+               files.map(|files| parse_files_with_timeout(files, timeout, &options.parse))
+            },
+         };
+         match scanned
+         {
+            Ok(scanned) => results.extend(scanned),
+            Err(err) => eprintln!("Can't read directory {}: {}", path, err),
+         }
+      }
+      else
+      {
+         let prefetch = match options.timeout
+         {
+            None => parse_prefetch_file_with_options(path, &options.parse),
+            Some(timeout) => parse_with_timeout(Path::new(path), timeout, &options.parse),
+         };
+         results.push((PathBuf::from(path), prefetch));
+      }
+   }
+   results
+}
+
+/// Parse `files` with a time budget for each, on the thread pool with the parallel feature
+fn parse_files_with_timeout(files : Vec<PathBuf>, timeout : Duration, options : &PrefetchParseOptions) -> Vec<(PathBuf, anyhow::Result<Prefetch>)>
+{
+   #[cfg(feature = "parallel")]
+   {
+      use rayon::prelude::*;
+      files.into_par_iter().map(|file| { let prefetch = parse_with_timeout(&file, timeout, options); (file, prefetch) }).collect()
+   }
+   #[cfg(not(feature = "parallel"))]
+   {
+      files.into_iter().map(|file| { let prefetch = parse_with_timeout(&file, timeout, options); (file, prefetch) }).collect()
+   }
+}
+
+/// Parse on a worker thread and give up after `timeout`, the worker of a file that timed out is left running detached
+fn parse_with_timeout(path : &Path, timeout : Duration, options : &PrefetchParseOptions) -> anyhow::Result<Prefetch>
+{
+   let (sender, receiver) = mpsc::channel();
+   let worker_path = path.to_path_buf();
+   let options = options.clone();
+   thread::spawn(move || { let _ = sender.send(parse_prefetch_file_with_options(worker_path, &options)); });
+
+   match receiver.recv_timeout(timeout)
+   {
+      Ok(prefetch) => prefetch,
+      Err(_) => Err(anyhow::anyhow!("Parsing timed out after {} ms", timeout.as_millis())),
+   }
+}
+
+/// Parsed prefetch of every input, failures and warnings are reported on stderr
+fn parsed_inputs(paths : &[String], options : &BatchOptions) -> Vec<(PathBuf, Prefetch)>
+{
+   parse_inputs(paths, options).into_iter().filter_map(|(path, result)| match result
+   {
+      Ok(prefetch) => { print_warnings(&path, &prefetch, options); Some((path, prefetch)) },
+      Err(err) => { eprintln!("{}: {}", path.display(), err); None },
+   }).collect()
+}
+
+fn print_warnings(path : &Path, prefetch : &Prefetch, options : &BatchOptions)
+{
+   if options.verbosity != Verbosity::Quiet
+   {
+      for warning in prefetch.warnings_at_least(options.min_severity)
+      {
+         eprintln!("{}: {}: {}", path.display(), warning.severity, warning);
+      }
+   }
+}
+
+/// Warnings of `prefetch` added to the output with --verbose, None otherwise
+fn output_warnings(prefetch : &Prefetch, options : &BatchOptions) -> Option<String>
+{
+   if options.verbosity != Verbosity::Verbose
+   {
+      return None
+   }
+   Some(prefetch.warnings_at_least(options.min_severity).map(|warning| warning.to_string()).collect::<Vec<_>>().join("; "))
+}
+
+/// Header of the warnings column appended to the CSV outputs with --verbose
+fn warnings_column(options : &BatchOptions) -> &'static str
+{
+   if options.verbosity == Verbosity::Verbose { ",warnings" } else { "" }
+}
+
+/// Print only the inputs that failed to parse, with their version when it can be read
+fn print_errors(paths : &[String], options : &BatchOptions)
+{
+   println!("source,version,kind,error");
+   for (path, result) in parse_inputs(paths, options)
+   {
+      if let Err(err) = result
+      {
+         let version = File::open(&path).ok().and_then(|file| PrefetchFormat::from_reader(&mut BufReader::new(file)).ok())
+                                        .map(|format| format.to_string()).unwrap_or_default();
+         print_csv_row(&[path.display().to_string(), version, error_kind(&err).to_string(), err.to_string()]);
+      }
+   }
+}
+
+fn error_kind(err : &anyhow::Error) -> &'static str
+{
+   match err.downcast_ref::<PrefetchError>()
+   {
+      Some(PrefetchError::CompressedUnsupported) => "CompressedUnsupported",
+      Some(PrefetchError::BadSignature(_)) => "BadSignature",
+      Some(PrefetchError::UnknownVersion(_)) => "UnknownVersion",
+      Some(PrefetchError::UnsupportedVersion(_)) => "UnsupportedVersion",
+      Some(PrefetchError::Truncated{ .. }) => "Truncated",
+      Some(PrefetchError::InvalidOffset{ .. }) => "InvalidOffset",
+      Some(PrefetchError::Anomaly(_)) => "Anomaly",
+      Some(PrefetchError::DecompressedSizeMismatch{ .. }) => "DecompressedSizeMismatch",
+      Some(PrefetchError::TooLarge{ .. }) => "TooLarge",
+      Some(PrefetchError::Io(_)) => "Io",
+      Some(PrefetchError::Other(_)) => "Other",
+      None if err.downcast_ref::<std::io::Error>().is_some() => "Io",
+      None => "Other",
+   }
+}
+
+/// Print one row per file using PECmd column names and date format
+fn print_pecmd_csv(paths : &[String], options : &BatchOptions)
+{
+   //PECmd has no warnings column, it's added after its columns
+   let warnings_header = if options.verbosity == Verbosity::Verbose { ",Warnings" } else { "" };
+   println!("{}{}", PECMD_HEADER, warnings_header);
+
+   for (path, prefetch) in parsed_inputs(paths, options)
+   {
+      let metadata = fs::metadata(&path).ok();
+      let source_time = |time : Option<SystemTime>| time.map(|time| pecmd_time(DateTime::<Utc>::from(time))).unwrap_or_default();
+
+      let mut row = vec![
+         String::new(),
+         path.display().to_string(),
+         source_time(metadata.as_ref().and_then(|metadata| metadata.created().ok())),
+         source_time(metadata.as_ref().and_then(|metadata| metadata.modified().ok())),
+         source_time(metadata.as_ref().and_then(|metadata| metadata.accessed().ok())),
+         prefetch.executable_name().to_string(),
+         format!("{:08X}", prefetch.header.hash()),
+         prefetch.header.file_size().to_string(),
+         pecmd_version(prefetch.header.version()).to_string(),
+         prefetch.run_count().to_string(),
+         prefetch.file_information.last_execution_time().map(pecmd_time).unwrap_or_default(),
+      ];
+      let previous_runs = prefetch.file_information.last_execution_times().iter().skip(1);
+      row.extend(previous_runs.map(|time| pecmd_time(*time)).chain(std::iter::repeat(String::new())).take(PECMD_PREVIOUS_RUNS));
+      for index in 0..PECMD_VOLUMES
+      {
+         let volume = prefetch.volumes.get(index);
+         row.push(volume.map(|volume| volume.device_path().to_string()).unwrap_or_default());
+         row.push(volume.map(|volume| volume.volume_serial_hex()).unwrap_or_default());
+         row.push(volume.and_then(|volume| volume.volume_creation_date()).map(pecmd_time).unwrap_or_default());
+      }
+      row.push(prefetch.directories().collect::<Vec<_>>().join(", "));
+      row.push(prefetch.files.join(", "));
+      row.push("False".to_string());
+
+      row.extend(output_warnings(&prefetch, options));
+      print_csv_row(&row);
+   }
+}
+
+/// Print every execution time of every input sorted chronologically
+fn print_timeline(paths : &[String], options : &BatchOptions)
+{
+   let mut timeline : Vec<(DateTime<Utc>, String, u32, Option<String>)> = Vec::new();
+
+   for (_, prefetch) in parsed_inputs(paths, options)
+   {
+      let warnings = output_warnings(&prefetch, options);
+      for timestamp in prefetch.all_timestamps()
+      {
+         timeline.push((timestamp, prefetch.executable_name().to_string(), prefetch.run_count(), warnings.clone()));
+      }
+   }
+   timeline.sort();
+
+   println!("timestamp,executable,run_count{}", warnings_column(options));
+   for (timestamp, executable, run_count, warnings) in timeline
+   {
+      let mut row = vec![timestamp.to_rfc3339(), executable, run_count.to_string()];
+      row.extend(warnings);
+      print_csv_row(&row);
+   }
+}
+
+/// Print aggregated statistics of every input
+fn print_stats(paths : &[String], options : &BatchOptions)
+{
+   let results = parse_inputs(paths, options);
+
+   let mut versions : BTreeMap<String, usize> = BTreeMap::new();
+   let mut integrity : BTreeMap<Confidence, usize> = BTreeMap::new();
+   let mut executables : BTreeSet<String> = BTreeSet::new();
+   let mut timestamps : Vec<DateTime<Utc>> = Vec::new();
+   let mut failures = 0;
+   let mut warnings = 0;
+   let mut volumes = 0;
+   let mut referenced_files = 0;
+   let mut referenced_path_bytes = 0;
+
+   for (path, result) in &results
+   {
+      match result
+      {
+         Ok(prefetch) =>
+         {
+            print_warnings(path, prefetch, options);
+            warnings += prefetch.warnings_at_least(options.min_severity).count();
+            *versions.entry(prefetch.header.version().to_string()).or_insert(0) += 1;
+            *integrity.entry(prefetch.integrity_report().confidence).or_insert(0) += 1;
+            executables.insert(prefetch.canonical_executable_name());
+            timestamps.extend(prefetch.all_timestamps());
+            volumes += prefetch.volume_count();
+            referenced_files += prefetch.referenced_file_count();
+            referenced_path_bytes += prefetch.total_referenced_path_bytes();
+         },
+         Err(err) => { eprintln!("{}: {}", path.display(), err); failures += 1 },
+      }
+   }
+
+   let format_time = |time : Option<&DateTime<Utc>>| time.map(|time| time.to_rfc3339()).unwrap_or_else(|| "-".into());
+
+   println!("files: {}", results.len());
+   for (version, count) in versions
+   {
+      println!("{}: {}", version, count);
+   }
+   println!("earliest execution: {}", format_time(timestamps.iter().min()));
+   println!("latest execution: {}", format_time(timestamps.iter().max()));
+   println!("distinct executables: {}", executables.len());
+   println!("volumes: {}", volumes);
+   println!("referenced files: {}", referenced_files);
+   println!("referenced path bytes: {}", referenced_path_bytes);
+   println!("parse failures: {}", failures);
+   for (confidence, count) in integrity.iter().rev()
+   {
+      println!("integrity {}: {}", confidence, count);
+   }
+   if options.verbosity == Verbosity::Verbose
+   {
+      println!("warnings: {}", warnings);
+   }
+}
+
+/// Print as json the executables of every input grouped by the serial of the volume they ran from
+fn print_volume_groups(paths : &[String], options : &BatchOptions)
+{
+   let mut groups : BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+   for (_, prefetch) in parsed_inputs(paths, options)
+   {
+      if let Some(volume) = prefetch.volume()
+      {
+         groups.entry(volume.volume_serial_hex()).or_default().insert(prefetch.canonical_executable_name());
+      }
+   }
+
+   println!("{}", serde_json::to_string(&groups).unwrap());
+}
+
+/**
+ *   Known-good executable of a baseline, without hash any file of that name is known
+ */
+#[derive(Deserialize)]
+struct BaselineEntry
+{
+   executable : String,
+   #[serde(default)]
+   hash : Option<String>,
+}
+
+impl BaselineEntry
+{
+   fn matches(&self, prefetch : &Prefetch) -> bool
+   {
+      if self.executable.trim().to_uppercase() != prefetch.canonical_executable_name()
+      {
+         return false
+      }
+      match &self.hash
+      {
+         Some(hash) => u32::from_str_radix(hash.trim_start_matches("0x"), 16).ok() == Some(prefetch.header.hash()),
+         None => true,
+      }
+   }
+}
+
+/// Mark every input as known or unknown depending if its executable is in the baseline
+fn print_baseline(baseline_path : &str, paths : &[String], options : &BatchOptions)
+{
+   let baseline : Vec<BaselineEntry> = match File::open(baseline_path).map_err(anyhow::Error::from)
+                                             .and_then(|file| Ok(serde_json::from_reader(BufReader::new(file))?))
+   {
+      Ok(baseline) => baseline,
+      Err(err) => { eprintln!("Can't read baseline {}: {}", baseline_path, err); return },
+   };
+
+   println!("source,executable,hash,status{}", warnings_column(options));
+   for (path, prefetch) in parsed_inputs(paths, options)
+   {
+      let status = if baseline.iter().any(|entry| entry.matches(&prefetch)) { "known" } else { "unknown" };
+      let mut row = vec![path.display().to_string(), prefetch.executable_name().to_string(),
+                         format!("{:08X}", prefetch.header.hash()), status.to_string()];
+      row.extend(output_warnings(&prefetch, options));
+      print_csv_row(&row);
+   }
+}
+
+/// Write one row per input in a parquet file
+#[cfg(feature = "parquet")]
+fn write_parquet(output_path : &str, paths : &[String], options : &BatchOptions)
+{
+   let parsed = parsed_inputs(paths, options);
+   let prefetches : Vec<&Prefetch> = parsed.iter().map(|(_, prefetch)| prefetch).collect();
+
+   let result = File::create(output_path).map_err(anyhow::Error::from)
+                  .and_then(|file| tap_plugin_prefetch::columnar::write_parquet(file, &prefetches));
+   if let Err(err) = result
+   {
+      eprintln!("Can't write {}: {}", output_path, err);
+   }
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_output_path : &str, _paths : &[String], _options : &BatchOptions)
+{
+   eprintln!("--parquet is unavailable, built without the parquet feature");
+}
+
+fn pecmd_time(time : DateTime<Utc>) -> String
+{
+   time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn pecmd_version(version : PrefetchVersion) -> &'static str
+{
+   match version
+   {
+      PrefetchVersion::WindowsXP => "WindowsXPOrWindows2003",
+      PrefetchVersion::WindowsVista => "WindowsVistaOrWindows7",
+      PrefetchVersion::Windows8 => "Windows8OrWindows2012",
+      PrefetchVersion::Windows10 => "Windows10OrWindows11",
+   }
+}
+
+fn print_csv_row(row : &[String])
+{
+   let row : Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+   println!("{}", row.join(","));
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a new line
+fn csv_field(field : &str) -> String
+{
+   if field.contains([',', '"', '\n', '\r'])
+   {
+      format!("\"{}\"", field.replace('"', "\"\""))
+   }
+   else
+   {
+      field.to_string()
+   }
+}
@@ -0,0 +1,174 @@
+//! Build `Prefetch` without a prefetch file, to test analysis code with readable fixtures
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Utc};
+
+use crate::{files_description, Prefetch, PrefetchHeader, PrefetchVersion, FileInformation, FileInformationHeader, FileReference, VolumeInformation};
+
+#[derive(Debug, Clone)]
+pub struct PrefetchBuilder
+{
+  version : PrefetchVersion,
+  executable_name : String,
+  hash : u32,
+  run_count : u32,
+  last_execution_time : DateTime<Utc>,
+  previous_execution_times : Vec<DateTime<Utc>>,
+  files : Vec<String>,
+  volumes : Vec<String>,
+  volume_serial_number : u32,
+  volume_creation_date : Option<DateTime<Utc>>,
+}
+
+impl Default for PrefetchBuilder
+{
+  fn default() -> Self
+  {
+    PrefetchBuilder{
+      version : PrefetchVersion::Windows8,
+      executable_name : String::new(),
+      hash : 0,
+      run_count : 0,
+      last_execution_time : DateTime::<Utc>::from(UNIX_EPOCH),
+      previous_execution_times : Vec::new(),
+      files : Vec::new(),
+      volumes : Vec::new(),
+      volume_serial_number : 0,
+      volume_creation_date : None,
+    }
+  }
+}
+
+impl PrefetchBuilder
+{
+  pub fn new() -> Self
+  {
+    PrefetchBuilder::default()
+  }
+
+  pub fn version(mut self, version : PrefetchVersion) -> Self
+  {
+    self.version = version;
+    self
+  }
+
+  pub fn executable_name(mut self, executable_name : &str) -> Self
+  {
+    self.executable_name = executable_name.to_string();
+    self
+  }
+
+  pub fn hash(mut self, hash : u32) -> Self
+  {
+    self.hash = hash;
+    self
+  }
+
+  pub fn run_count(mut self, run_count : u32) -> Self
+  {
+    self.run_count = run_count;
+    self
+  }
+
+  pub fn last_execution_time(mut self, last_execution_time : DateTime<Utc>) -> Self
+  {
+    self.last_execution_time = last_execution_time;
+    self
+  }
+
+  /// Runs before the last one, newest first
+  pub fn previous_execution_times(mut self, previous_execution_times : Vec<DateTime<Utc>>) -> Self
+  {
+    self.previous_execution_times = previous_execution_times;
+    self
+  }
+
+  pub fn files(mut self, files : Vec<String>) -> Self
+  {
+    self.files = files;
+    self
+  }
+
+  /// Directories of the single volume of the built prefetch
+  pub fn volumes(mut self, volumes : Vec<String>) -> Self
+  {
+    self.volumes = volumes;
+    self
+  }
+
+  pub fn volume_serial_number(mut self, volume_serial_number : u32) -> Self
+  {
+    self.volume_serial_number = volume_serial_number;
+    self
+  }
+
+  pub fn volume_creation_date(mut self, volume_creation_date : DateTime<Utc>) -> Self
+  {
+    self.volume_creation_date = Some(volume_creation_date);
+    self
+  }
+
+  /// Offsets and sizes are left to zero as there is no underlying file
+  pub fn build(self) -> Prefetch
+  {
+    let header = PrefetchHeader{
+      version : self.version,
+      version_value : self.version.value(),
+      version_name : self.version.name().to_string(),
+      signature : "SCCA".into(),
+      file_size : 0,
+      file_name : self.executable_name,
+      hash : self.hash,
+      first_file_path_offset : 0,
+      first_file_path_size : 0,
+      volume_information_offset : 0,
+      volume_information_size : 0,
+    };
+
+    let mut last_execution_times = vec![self.last_execution_time];
+    last_execution_times.extend(self.previous_execution_times);
+    let file_information = FileInformation{
+      last_execution_time : Some(self.last_execution_time),
+      last_execution_times,
+      number_of_execution : self.run_count,
+      invalid_execution_times : Vec::new(),
+      run_count_offset : 0,
+    };
+
+    let volume_information = VolumeInformation{
+      volume_path_offset : 0,
+      volume_path_size : 0,
+      volume_creation_date : self.volume_creation_date,
+      volume_creation_filetime : 0,
+      volume_serial_number : self.volume_serial_number,
+      blob1_offset : 0,
+      blob1_size : 0,
+      folder_path_offset : 0,
+      folder_path_count : self.volumes.len() as u32,
+      device_path : String::new(),
+      directories : self.volumes,
+    };
+
+    let files_description = files_description(&header).to_string();
+    let file_references = vec![FileReference::default(); self.files.len()];
+
+    Prefetch{
+      header : Arc::new(header),
+      file_information : Arc::new(file_information),
+      files : self.files,
+      volumes : vec![Arc::new(volume_information)],
+      compressed : false,
+      warnings : Vec::new(),
+      pf_file_created : None,
+      pf_file_modified : None,
+      files_description,
+      hash_is_valid : None,
+      file_information_header : Arc::new(FileInformationHeader{ volume_count : 1, ..Default::default() }),
+      metrics : Vec::new(),
+      file_references,
+      #[cfg(feature = "research")]
+      raw : Vec::new(),
+    }
+  }
+}
@@ -0,0 +1,35 @@
+//! Parquet export of parsed prefetch, one row per file with typed columns for analytical stores.
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, StringArray, TimestampMicrosecondArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+
+use crate::Prefetch;
+
+/// Write `prefetches` as a single row group
+pub fn write_parquet<W : Write + Send>(writer : W, prefetches : &[&Prefetch]) -> anyhow::Result<()>
+{
+  let schema = Arc::new(Schema::new(vec![
+    Field::new("name", DataType::Utf8, false),
+    Field::new("run_count", DataType::UInt32, false),
+    Field::new("last_execution", DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), true),
+    Field::new("volume_serial", DataType::UInt32, false),
+  ]));
+
+  let names = StringArray::from_iter_values(prefetches.iter().map(|prefetch| prefetch.canonical_executable_name()));
+  let run_counts = UInt32Array::from_iter_values(prefetches.iter().map(|prefetch| prefetch.run_count()));
+  let last_executions = prefetches.iter().map(|prefetch| prefetch.file_information.last_execution_time().map(|time| time.timestamp_micros()))
+                          .collect::<TimestampMicrosecondArray>().with_timezone("UTC");
+  let volume_serials = UInt32Array::from_iter_values(prefetches.iter().map(|prefetch| prefetch.volume().map(|volume| volume.volume_serial_number()).unwrap_or_default()));
+
+  let columns : Vec<ArrayRef> = vec![Arc::new(names), Arc::new(run_counts), Arc::new(last_executions), Arc::new(volume_serials)];
+  let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+  let mut writer = ArrowWriter::try_new(writer, schema, None)?;
+  writer.write(&batch)?;
+  writer.close()?;
+  Ok(())
+}
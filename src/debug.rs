@@ -0,0 +1,93 @@
+//! Raw offsets of the structures of a prefetch, read without any validation so they can be inspected
+//! when the parsing fails.
+use std::io::SeekFrom;
+use std::sync::Arc;
+
+use byteorder::ReadBytesExt;
+use tap::vfile::VFile;
+use tap_derive::Reflect;
+
+use crate::{Endian, Prefetch, PrefetchVersion, VolumeInformation};
+use crate::mam::MamReader;
+
+/// Volume entries read at most, a corrupted count must not make the attribute huge
+const MAX_DEBUG_VOLUMES : u32 = 64;
+
+/**
+ *   Offsets stored in the header, added as the `prefetch_debug` attribute by the plugin `debug` argument
+ */
+#[derive(Debug, Default, Reflect)]
+pub struct PrefetchDebug
+{
+  pub version_value : u32,
+  /// Offsets are the ones of the decompressed data
+  pub compressed : bool,
+  pub first_file_path_offset : u32,
+  pub first_file_path_size : u32,
+  pub volume_information_offset : u32,
+  pub volume_count : u32,
+  pub volume_information_size : u32,
+  pub volumes : Vec<Arc<VolumeDebug>>,
+  /// Error that stopped the reading, the fields that weren't read yet are zero
+  pub error : Option<String>,
+}
+
+/**
+ *   Offsets stored in a volume entry
+ */
+#[derive(Debug, Default, Reflect)]
+pub struct VolumeDebug
+{
+  /// Offset of the entry in the file
+  pub offset : u64,
+  pub folder_path_offset : u32,
+  pub folder_path_count : u32,
+}
+
+impl PrefetchDebug
+{
+  /// Read every offset up to the first error, which is kept in `error`
+  pub fn from_reader<T : VFile>(file : &mut T) -> PrefetchDebug
+  {
+    let mut debug = PrefetchDebug::default();
+    if let Err(err) = debug.read(file)
+    {
+      debug.error = Some(err.to_string());
+    }
+    debug
+  }
+
+  fn read<T : VFile>(&mut self, file : &mut T) -> anyhow::Result<()>
+  {
+    if Prefetch::is_compressed(file)?
+    {
+      self.compressed = true;
+      return self.read_uncompressed(&mut MamReader::new(&mut *file)?)
+    }
+    self.read_uncompressed(file)
+  }
+
+  fn read_uncompressed<T : VFile>(&mut self, file : &mut T) -> anyhow::Result<()>
+  {
+    file.seek(SeekFrom::Start(0))?;
+    self.version_value = file.read_u32::<Endian>()?;
+
+    file.seek(SeekFrom::Start(0x64))?;
+    self.first_file_path_offset = file.read_u32::<Endian>()?;
+    self.first_file_path_size = file.read_u32::<Endian>()?;
+    self.volume_information_offset = file.read_u32::<Endian>()?;
+    self.volume_count = file.read_u32::<Endian>()?;
+    self.volume_information_size = file.read_u32::<Endian>()?;
+
+    let entry_size = PrefetchVersion::from_value(self.version_value).map(VolumeInformation::entry_size).unwrap_or(0x68);
+    for index in 0..self.volume_count.min(MAX_DEBUG_VOLUMES) as u64
+    {
+      let offset = self.volume_information_offset as u64 + index * entry_size;
+      file.seek(SeekFrom::Start(offset + 0x1c))?;
+      let folder_path_offset = file.read_u32::<Endian>()?;
+      let folder_path_count = file.read_u32::<Endian>()?;
+      self.volumes.push(Arc::new(VolumeDebug{ offset, folder_path_offset, folder_path_count }));
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,122 @@
+//! Hash of the executable path stored in the prefetch header and used in the prefetch file name.
+//! The hash is computed on the upper case UTF-16 little-endian bytes of the full device path
+//! (`\DEVICE\HARDDISKVOLUME1\WINDOWS\SYSTEM32\NOTEPAD.EXE`).
+//!
+//! Algorithm used by each version :
+//!
+//! * Windows XP / 2003 (0x11) : XP
+//! * Windows Vista (0x17) : Vista, Windows 7 share the same version but use the 2008 algorithm
+//! * Windows 8 (0x1a) : 2008
+//! * Windows 10 / 11 : 2008, some builds went back to the XP algorithm
+use serde::Serialize;
+
+use crate::PrefetchVersion;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HashAlgorithm
+{
+  Xp,
+  Vista,
+  Windows2008,
+}
+
+impl HashAlgorithm
+{
+  /// Algorithms that can be used by `version`, most common first
+  pub fn candidates(version : PrefetchVersion) -> &'static [HashAlgorithm]
+  {
+    match version
+    {
+      PrefetchVersion::WindowsXP => &[HashAlgorithm::Xp],
+      PrefetchVersion::WindowsVista => &[HashAlgorithm::Vista, HashAlgorithm::Windows2008],
+      PrefetchVersion::Windows8 => &[HashAlgorithm::Windows2008],
+      PrefetchVersion::Windows10 => &[HashAlgorithm::Windows2008, HashAlgorithm::Xp],
+    }
+  }
+
+  /// Hash of `path`, the path is converted to upper case before hashing
+  pub fn hash(&self, path : &str) -> u32
+  {
+    let bytes : Vec<u8> = path.to_uppercase().encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+
+    match self
+    {
+      HashAlgorithm::Xp => xp_hash(&bytes),
+      HashAlgorithm::Vista => vista_hash(&bytes),
+      HashAlgorithm::Windows2008 => windows2008_hash(&bytes),
+    }
+  }
+}
+
+/// Hash of `path` with the most common algorithm of `version`
+pub fn prefetch_hash(path : &str, version : PrefetchVersion) -> u32
+{
+  HashAlgorithm::candidates(version)[0].hash(path)
+}
+
+/**
+ *   Result of a hash check, with the algorithm so a wrong algorithm can be told apart from a tampered file
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct HashVerification
+{
+  pub verified : bool,
+  /// Matching algorithm, or the most common one of the version if none matched
+  pub algorithm : HashAlgorithm,
+  pub computed : u32,
+  pub stored : u32,
+}
+
+fn xp_hash(bytes : &[u8]) -> u32
+{
+  let mut hash : u32 = 0;
+  for byte in bytes
+  {
+    hash = hash.wrapping_mul(37).wrapping_add(*byte as u32);
+  }
+
+  hash = hash.wrapping_mul(314159269);
+  if hash > 0x80000000
+  {
+    hash = hash.wrapping_neg();
+  }
+  hash % 1000000007
+}
+
+fn vista_hash(bytes : &[u8]) -> u32
+{
+  let mut hash : u32 = 314159;
+  for byte in bytes
+  {
+    hash = hash.wrapping_mul(37).wrapping_add(*byte as u32);
+  }
+  hash
+}
+
+fn windows2008_hash(bytes : &[u8]) -> u32
+{
+  let mut hash : u32 = 314159;
+  let mut index = 0;
+
+  while index + 8 < bytes.len()
+  {
+    let byte = |offset : usize| bytes[index + offset] as u32;
+
+    let mut value = byte(1).wrapping_mul(37);
+    for offset in 2..7
+    {
+      value = value.wrapping_add(byte(offset)).wrapping_mul(37);
+    }
+    value = value.wrapping_add(byte(0).wrapping_mul(442596621));
+    value = value.wrapping_add(byte(7));
+
+    hash = value.wrapping_sub(hash.wrapping_mul(803794207));
+    index += 8;
+  }
+
+  for byte in &bytes[index..]
+  {
+    hash = hash.wrapping_mul(37).wrapping_add(*byte as u32);
+  }
+  hash
+}
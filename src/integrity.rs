@@ -0,0 +1,89 @@
+//! Aggregation of the consistency checks of a parsed prefetch into a single confidence level for triage.
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+
+use crate::{Prefetch, DEFAULT_MAX_RUN_COUNT};
+use crate::warning::Severity;
+
+/**
+ *   Confidence in the parsed values
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence
+{
+  Low,
+  Medium,
+  High,
+}
+
+impl fmt::Display for Confidence
+{
+  fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result
+  {
+    match self
+    {
+      Confidence::Low => write!(f, "low"),
+      Confidence::Medium => write!(f, "medium"),
+      Confidence::High => write!(f, "high"),
+    }
+  }
+}
+
+/**
+ *   Result of each check, `confidence` is high when all pass, medium when one fails and low otherwise
+ *   or when the signature is wrong
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport
+{
+  pub signature_valid : bool,
+  /// Structures end before the file size stored in the header
+  pub file_size_consistent : bool,
+  /// File path list doesn't overlap the volume information
+  pub offsets_in_bounds : bool,
+  pub run_count_plausible : bool,
+  /// None when the executable path isn't a device path the hash can be computed on
+  pub hash_verified : Option<bool>,
+  pub warning_count : usize,
+  pub confidence : Confidence,
+}
+
+impl Prefetch
+{
+  pub fn integrity_report(&self) -> IntegrityReport
+  {
+    let header = &self.header;
+    let signature_valid = header.signature_is_valid();
+
+    let file_size_consistent = self.file_information_header().sections_end(header.version()) <= header.file_size as u64;
+
+    let offsets_in_bounds = header.file_path_size_valid();
+    let run_count_plausible = self.run_count() <= DEFAULT_MAX_RUN_COUNT;
+    let hash_verified = self.path_hash_is_valid();
+
+    //informational warnings only tell how the file was parsed
+    let no_anomaly = self.warnings_at_least(Severity::Warning).next().is_none();
+    let failures = [file_size_consistent, offsets_in_bounds, run_count_plausible, hash_verified.unwrap_or(true), no_anomaly]
+                   .iter().filter(|passed| !**passed).count();
+    let confidence = match failures
+    {
+      _ if !signature_valid => Confidence::Low,
+      0 => Confidence::High,
+      1 => Confidence::Medium,
+      _ => Confidence::Low,
+    };
+
+    IntegrityReport{
+      signature_valid,
+      file_size_consistent,
+      offsets_in_bounds,
+      run_count_plausible,
+      hash_verified,
+      warning_count : self.warnings.len(),
+      confidence,
+    }
+  }
+}
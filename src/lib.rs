@@ -1,39 +1,103 @@
 //! Prefetch plugin take a VFile attribute return from a node and  add the result of an prefetch function to the attribute of this node
 #![allow(dead_code)]
 
+pub mod mam;
+pub mod hash;
+pub mod readyboot;
+pub mod offset;
+pub mod source;
+pub mod integrity;
+pub mod mft;
+pub mod warning;
+pub mod debug;
+mod writer;
+#[cfg(feature = "builder")]
+pub mod builder;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+
 use std::sync::Arc;
-use std::io::BufReader;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::io::{BufReader, Cursor, Read};
 use std::io::SeekFrom;
-use std::fmt::Debug;
+use std::fmt;
+use std::str::FromStr;
 
 use tap::config_schema;
 use tap::plugin;
 use tap::plugin::{PluginInfo, PluginInstance, PluginConfig, PluginArgument, PluginResult, PluginEnvironment};
-use tap::vfile::{VFile, read_utf16_exact, read_sized_utf16, read_utf16_list};
+use tap::vfile::{VFile, VFileBuilder, read_utf16_exact, read_sized_utf16, read_utf16_list};
 use tap::reflect::{ReflectStruct};
 use tap::value::Value;
 use tap::datetime::WindowsTimestamp;
 use tap::error::RustructError;
 use tap::tree::{TreeNodeId, TreeNodeIdSchema};
+use tap::node::Node;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Serialize, Deserialize};
 use schemars::{JsonSchema};
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
 use tap_derive::Reflect;
 
+use crate::hash::{HashAlgorithm, HashVerification};
+use crate::offset::OffsetReader;
+use crate::integrity::Confidence;
+use crate::warning::{ParseWarning, Severity};
+use crate::debug::PrefetchDebug;
+
 plugin!("prefetch", "Windows", "Parse prefetch file", PrefetchPlugin, Arguments);
 
+/// Prefetch files are always stored in little-endian
+type Endian = LittleEndian;
+
+/// Size of the header and of the file information fields common to all versions
+pub const MIN_PREFETCH_SIZE : u64 = 0x84;
+/// Larger files are most likely not prefetch, real ones are a few hundred KiB at most
+pub const MAX_REASONABLE_PREFETCH_SIZE : u64 = 16 * 1024 * 1024;
+/// Default of `PrefetchParseOptions::max_run_count`
+pub const DEFAULT_MAX_RUN_COUNT : u32 = 100_000;
+/// Number of execution times stored by Windows 8 and later
+pub const EXECUTION_TIME_COUNT : usize = 8;
+/// Default of `PrefetchParseOptions::read_buffer_size`, most prefetch fit entirely in it
+pub const DEFAULT_READ_BUFFER_SIZE : usize = 64 * 1024;
+/// Bytes searched for the signature when `PrefetchParseOptions::scan_signature` is set
+const SIGNATURE_SCAN_SIZE : usize = 0x4000;
+
+/// Signature found at offset 4 of an uncompressed prefetch file
+pub const SCCA_SIGNATURE : &[u8; 4] = b"SCCA";
+/// Signature found at offset 0 of a Windows 10 compressed prefetch file
+pub const MAM_SIGNATURE : &[u8; 4] = b"MAM\x04";
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Arguments
 {
   #[schemars(with = "TreeNodeIdSchema")] 
   file : TreeNodeId,
+  /// Also add run count, last execution time and executable name as separate attributes
+  #[serde(default)]
+  flatten : bool,
+  /// Add last execution time and volume creation date as datetime attributes, for time range queries
+  #[serde(default)]
+  timestamps : bool,
+  /// Also create a child node for each volume and for each referenced file under its volume,
+  /// the `prefetch` attribute is still added
+  #[serde(default)]
+  file_nodes : bool,
+  /// Also add the raw header and volume entry offsets as the `prefetch_debug` attribute, even when parsing fails
+  #[serde(default)]
+  debug : bool,
 }
 
 #[derive(Debug, Serialize, Deserialize,Default)]
 pub struct Results
 {
+  /// Node of the parsed file
+  source_node_id : Option<TreeNodeId>,
+  executable_name : String,
 }
 
 #[derive(Default)]
@@ -48,18 +112,250 @@ impl PrefetchPlugin
     let file_node = env.tree.get_node_from_id(args.file).ok_or(RustructError::ArgumentNotFound("file"))?;
     let data = file_node.value().get_value("data").ok_or(RustructError::ValueNotFound("data"))?;
     let data_builder = data.try_as_vfile_builder().ok_or(RustructError::ValueTypeMismatch)?;
-    let file = data_builder.open()?;
 
-    let mut file = BufReader::new(file); 
-    let prefetch = match Prefetch::from_file(&mut file)
+    if args.debug
+    {
+      let debug = PrefetchDebug::from_reader(&mut data_builder.open()?);
+      file_node.value().add_attribute("prefetch_debug", Arc::new(debug), None);
+    }
+
+    let mut prefetch = match Prefetch::from_vfile_builder(&*data_builder, &file_node.name(), &PrefetchParseOptions::default())
     {
        Ok(prefetch) => prefetch,
-       Err(err) => { file_node.value().add_attribute(self.name(), None, None); return Err(err) },
+       Err(err) =>
+       {
+         //let a bulk scan continue past the formats we can't parse yet
+         let unsupported = match &err
+         {
+           PrefetchError::CompressedUnsupported => "MAM variant".to_string(),
+           PrefetchError::UnsupportedVersion(version) => version.to_string(),
+           PrefetchError::UnknownVersion(value) => format!("{:#x}", value),
+           _ => { file_node.value().add_attribute(self.name(), None, None); return Err(err.into()) },
+         };
+
+         log::warn!("prefetch: unsupported format {}", unsupported);
+         file_node.value().add_attribute(self.name(), Arc::new(Unsupported{ unsupported }), None);
+         return Ok(Results{ source_node_id : Some(args.file), executable_name : String::new() })
+       },
     };
+
+    //timestamps of the .pf file itself set by the filesystem plugin, to correlate with the execution times
+    let node_timestamp = |paths : &[&[&str]]| paths.iter().find_map(|path|
+    {
+      let (name, names) = path.split_first()?;
+      let mut value = file_node.value().get_value(name)?;
+      for name in names
+      {
+        value = match value
+        {
+          Value::ReflectStruct(reflect) => reflect.get_value(name)?,
+          _ => return None,
+        };
+      }
+      match value
+      {
+        Value::DateTime(time) => Some(time),
+        _ => None,
+      }
+    });
+    prefetch.pf_file_created = node_timestamp(NODE_CREATED_PATHS);
+    prefetch.pf_file_modified = node_timestamp(NODE_MODIFIED_PATHS);
+
+    if args.flatten
+    {
+      file_node.value().add_attribute("run_count", prefetch.run_count(), None);
+      if let Some(last_execution_time) = prefetch.file_information.last_execution_time()
+      {
+        file_node.value().add_attribute("last_execution_time", last_execution_time, None);
+      }
+      file_node.value().add_attribute("executable_name", prefetch.executable_name().to_string(), None);
+    }
+
+    if args.timestamps
+    {
+      if let Some(last_execution_time) = prefetch.file_information.last_execution_time().filter(|_| !args.flatten)
+      {
+        file_node.value().add_attribute("last_execution_time", Value::DateTime(last_execution_time), None);
+      }
+      if let Some(volume_creation_date) = prefetch.volume().and_then(|volume| volume.volume_creation_date())
+      {
+        file_node.value().add_attribute("volume_creation_date", Value::DateTime(volume_creation_date), None);
+      }
+    }
       
+    if args.file_nodes
+    {
+      PrefetchPlugin::add_file_nodes(&env, args.file, &prefetch)?;
+    }
+
+    if !prefetch.warnings.is_empty()
+    {
+      let warnings : Vec<String> = prefetch.warnings.iter().map(|warning| warning.to_string()).collect();
+      file_node.value().add_attribute("warnings", warnings, None);
+    }
+
+    let executable_name = prefetch.executable_name().to_string();
     file_node.value().add_attribute("prefetch", Arc::new(prefetch), None);
 
-    Ok(Results{})
+    Ok(Results{ source_node_id : Some(args.file), executable_name })
+  }
+
+  /// Add a node for each volume under `parent` and a node with a `path` attribute for each file under the volume
+  /// its path start with, files not matching any volume are added under `parent`
+  fn add_file_nodes(env : &PluginEnvironment, parent : TreeNodeId, prefetch : &Prefetch) -> anyhow::Result<()>
+  {
+    let mut volume_nodes : Vec<TreeNodeId> = Vec::new();
+    for volume in &prefetch.volumes
+    {
+      let name = match volume.device_path()
+      {
+        "" => volume.volume_serial_hex(),
+        device_path => device_path.to_string(),
+      };
+      let node = Node::new(name);
+      node.value().add_attribute("volume_serial_number", volume.volume_serial_number(), None);
+      volume_nodes.push(env.tree.add_child(parent, node)?);
+    }
+
+    for path in &prefetch.files
+    {
+      let node = Node::new(path.rsplit('\\').next().unwrap_or(path));
+      node.value().add_attribute("path", path.clone(), None);
+      env.tree.add_child(prefetch.volume_index(path).map(|index| volume_nodes[index]).unwrap_or(parent), node)?;
+    }
+    Ok(())
+  }
+}
+
+/// Attributes of the node holding its creation time, as set by the ntfs or the local filesystem plugins
+const NODE_CREATED_PATHS : &[&[&str]] = &[&["ntfs", "standard_information", "creation_time"], &["creation_time"]];
+/// Attributes of the node holding its modification time
+const NODE_MODIFIED_PATHS : &[&[&str]] = &[&["ntfs", "standard_information", "altered_time"], &["modification_time"]];
+
+/**
+ *   Prefetch parsing error, returned by the `Prefetch` and `PrefetchHeader` constructors
+ */
+#[derive(Debug)]
+pub enum PrefetchError
+{
+  /// File start with a MAM header other than the one of Windows 10 compressed prefetch
+  CompressedUnsupported,
+  /// Bytes at offset 4 aren't `SCCA`
+  BadSignature(String),
+  /// Version dword doesn't match any known version
+  UnknownVersion(u32),
+  /// Version is known but has no file information reader
+  UnsupportedVersion(PrefetchVersion),
+  /// End of file reached, `offset` is the position of the reader when the read failed
+  Truncated{ offset : u64 },
+  /// Header `field` point after the end of file
+  InvalidOffset{ field : &'static str, offset : u64 },
+  /// Anomaly found while parsing in strict mode
+  Anomaly(String),
+  /// Compressed stream decoded to less data than the size stored in the MAM header
+  DecompressedSizeMismatch{ expected : u64, actual : u64 },
+  /// MAM header declare more decompressed data than `MAX_REASONABLE_PREFETCH_SIZE`
+  TooLarge{ size : u64 },
+  Io(std::io::Error),
+  Other(anyhow::Error),
+}
+
+impl PrefetchError
+{
+  /// Typed error of a parser failure, `file` is where the failing read left it
+  fn from_parse<T : VFile + ?Sized>(err : anyhow::Error, file : &mut T) -> PrefetchError
+  {
+    match PrefetchError::from(err)
+    {
+      PrefetchError::Io(err) if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+        PrefetchError::Truncated{ offset : file.stream_position().unwrap_or_default() },
+      err => err,
+    }
+  }
+}
+
+impl From<anyhow::Error> for PrefetchError
+{
+  fn from(err : anyhow::Error) -> PrefetchError
+  {
+    let err = match err.downcast::<PrefetchError>()
+    {
+      Ok(err) => return err,
+      Err(err) => err,
+    };
+    match err.downcast::<std::io::Error>()
+    {
+      Ok(err) => PrefetchError::Io(err),
+      Err(err) => PrefetchError::Other(err),
+    }
+  }
+}
+
+impl fmt::Display for PrefetchError
+{
+  fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result
+  {
+    match self
+    {
+      PrefetchError::CompressedUnsupported => write!(f, "Unsupported compressed (MAM) prefetch variant"),
+      PrefetchError::BadSignature(signature) => write!(f, "Invalid prefetch signature {}", signature),
+      PrefetchError::UnknownVersion(value) => write!(f, "Can't match Prefetch version {:#x}", value),
+      PrefetchError::UnsupportedVersion(version) => write!(f, "Unsupported prefetch version {}", version),
+      PrefetchError::Truncated{ offset } => write!(f, "Prefetch truncated at offset {:#x}", offset),
+      PrefetchError::InvalidOffset{ field, offset } => write!(f, "{} {:#x} is after the end of file", field, offset),
+      PrefetchError::Anomaly(warning) => write!(f, "Prefetch anomaly : {}", warning),
+      PrefetchError::DecompressedSizeMismatch{ expected, actual } =>
+        write!(f, "Corrupted compressed prefetch, decompressed {} bytes instead of {}", actual, expected),
+      PrefetchError::TooLarge{ size } =>
+        write!(f, "Compressed prefetch declare {} bytes of decompressed data, more than {}", size, MAX_REASONABLE_PREFETCH_SIZE),
+      PrefetchError::Io(err) => write!(f, "{}", err),
+      PrefetchError::Other(err) => write!(f, "{}", err),
+    }
+  }
+}
+
+impl std::error::Error for PrefetchError {}
+
+/**
+ *   Attribute added by the plugin on file it can't parse yet
+ */
+#[derive(Debug, Reflect)]
+pub struct Unsupported
+{
+  unsupported : String,
+}
+
+/**
+ *   Options of Prefetch::from_file_with_options
+ */
+#[derive(Debug, Clone)]
+pub struct PrefetchParseOptions
+{
+  /// Read the whole file in memory before parsing, for sources where seeking is slow
+  pub buffer_stream : bool,
+  /// Leave `files` empty instead of decoding the file path list, when only metadata are needed
+  pub skip_file_list : bool,
+  /// Run count above which the value is considered corrupted or manipulated
+  pub max_run_count : u32,
+  /// Fail on the first anomaly (warning of severity `Warning` or `Error`) instead of adding it to `warnings`
+  pub strict : bool,
+  /// Search the start of the file for the header when it's preceded by padding, for carved files
+  pub scan_signature : bool,
+  /// Capacity of the buffer wrapping files opened by the parser
+  pub read_buffer_size : usize,
+  /// Parse with the layout of this version whatever the version dword is, to attempt parsing new builds
+  pub assume_version : Option<PrefetchVersion>,
+  /// Reject files whose signature isn't `SCCA`, when disabled a wrong signature is only a warning
+  pub verify_signature : bool,
+}
+
+impl Default for PrefetchParseOptions
+{
+  fn default() -> PrefetchParseOptions
+  {
+    PrefetchParseOptions{ buffer_stream : false, skip_file_list : false, max_run_count : DEFAULT_MAX_RUN_COUNT, strict : false,
+                          scan_signature : false, read_buffer_size : DEFAULT_READ_BUFFER_SIZE, assume_version : None,
+                          verify_signature : true }
   }
 }
 
@@ -67,180 +363,1431 @@ impl PrefetchPlugin
  *   Prefetch parser
  */
 #[derive(Debug, Reflect)] //reflect ...
+#[cfg_attr(feature = "cache", derive(Serialize, Deserialize))]
 pub struct Prefetch
 {
   pub header : Arc<PrefetchHeader>,
   pub file_information : Arc<FileInformation>,
-  pub volume_information : Arc<VolumeInformation>,
   pub files : Vec<String>,
-  pub volumes : Vec<String>,
+  /// Volumes the files were loaded from, with their directories
+  pub volumes : Vec<Arc<VolumeInformation>>,
+  pub compressed : bool,
+  /// Non fatal problems found while parsing, the plugin add their messages as the `warnings` attribute
+  #[reflect(skip)]
+  pub warnings : Vec<ParseWarning>,
+  /// Creation time of the .pf file from the filesystem, only set by the plugin
+  pub pf_file_created : Option<DateTime<Utc>>,
+  /// Modification time of the .pf file from the filesystem, only set by the plugin
+  pub pf_file_modified : Option<DateTime<Utc>>,
+  /// Meaning of `files`, the boot trace list the files loaded while booting instead of by an executable
+  pub files_description : String,
+  /// Header hash matches the hash of the executable device path or the hash of the .pf file name,
+  /// None when neither is available (Windows 8 and later record volume GUID paths that can't be hashed)
+  pub hash_is_valid : Option<bool>,
+  #[reflect(skip)]
+  file_information_header : Arc<FileInformationHeader>,
+  #[reflect(skip)]
+  metrics : Vec<FileMetric>,
+  /// MFT entry and sequence number of each of `files`, at the same index
+  #[reflect(skip)]
+  pub file_references : Vec<FileReference>,
+  /// Whole file, kept for `raw_u32_at`
+  #[cfg(feature = "research")]
+  #[reflect(skip)]
+  raw : Vec<u8>,
 }
 
 impl Prefetch
 {
-  pub fn from_file<T : VFile>(file : &mut T) -> anyhow::Result<Prefetch>
+  pub fn from_file<T : VFile>(file : &mut T) -> Result<Prefetch, PrefetchError>
+  {
+    Prefetch::from_file_with_options(file, &PrefetchParseOptions::default())
+  }
+
+  /// Parse a prefetch already loaded in memory, compressed or not
+  pub fn from_bytes(data : &[u8]) -> Result<Prefetch, PrefetchError>
+  {
+    Prefetch::from_file(&mut Cursor::new(data))
+  }
+
+  /// Parse only the header (version, signature, executable name, hash and structure offsets) without reading
+  /// the body, so it works on versions without a file information reader
+  pub fn read_header<T : VFile>(file : &mut T) -> Result<PrefetchHeader, PrefetchError>
+  {
+    if Prefetch::is_compressed(file).map_err(|err| PrefetchError::from_parse(err, file))?
+    {
+      //only the start of the stream is decompressed
+      return PrefetchHeader::from_reader(&mut mam::MamReader::new(file)?)
+    }
+    PrefetchHeader::from_reader(file)
+  }
+
+  /// Open, buffer and parse the file of `builder`, the single place where the plugin open its input,
+  /// `file_name` is the name of the node
+  pub fn from_vfile_builder<B : VFileBuilder + ?Sized>(builder : &B, file_name : &str, options : &PrefetchParseOptions) -> Result<Prefetch, PrefetchError>
+  {
+    let file = builder.open()?;
+    let mut file = BufReader::with_capacity(options.read_buffer_size, file);
+
+    Prefetch::from_named_file(&mut file, file_name, options)
+  }
+
+  /// Parse `file` and fallback to its .pf `file_name` to check the header hash when the executable path can't be hashed
+  pub fn from_named_file<T : VFile>(file : &mut T, file_name : &str, options : &PrefetchParseOptions) -> Result<Prefetch, PrefetchError>
+  {
+    let mut prefetch = Prefetch::from_file_with_options(file, options)?;
+    prefetch.check_file_name_hash(file_name);
+    Ok(prefetch)
+  }
+
+  /// Parse a stream that can't seek by reading it entirely in memory first
+  pub fn from_stream<R : Read>(reader : &mut R, options : &PrefetchParseOptions) -> Result<Prefetch, PrefetchError>
+  {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).map_err(PrefetchError::Io)?;
+
+    let options = PrefetchParseOptions{ buffer_stream : false, ..options.clone() };
+    Prefetch::from_file_with_options(&mut Cursor::new(data), &options)
+  }
+
+  /// Parse every prefetch concatenated in `file`, records are found by their signature and
+  /// skipped using their `file_size`, the last one can be truncated
+  pub fn parse_stream_all<T : VFile>(file : &mut T) -> Vec<anyhow::Result<Prefetch>>
+  {
+    let mut records = Vec::new();
+    if let Err(err) = Prefetch::parse_records(file, &mut records)
+    {
+      records.push(Err(err));
+    }
+    records
+  }
+
+  fn parse_records<T : VFile>(file : &mut T, records : &mut Vec<anyhow::Result<Prefetch>>) -> anyhow::Result<()>
+  {
+    let size = file.seek(SeekFrom::End(0))?;
+    let options = PrefetchParseOptions::default();
+    let mut start = 0;
+
+    while start < size
+    {
+      //search by chunk, overlapping so a signature across two chunks is still found
+      let offset = match Prefetch::find_header(file, start, SIGNATURE_SCAN_SIZE as u64)?
+      {
+        Some(offset) => offset,
+        None => { start += SIGNATURE_SCAN_SIZE as u64 - 8; continue },
+      };
+
+      let record = Prefetch::from_file_at(file, offset, &options);
+      start = match &record
+      {
+        //a zero or too small size would loop on the same record
+        Ok(prefetch) if prefetch.header.file_size() as u64 >= MIN_PREFETCH_SIZE => offset + prefetch.header.file_size() as u64,
+        _ => offset + 8,
+      };
+      records.push(record.map_err(anyhow::Error::from).map(|mut prefetch|
+      {
+        if start > size
+        {
+          prefetch.warnings.push(ParseWarning::new(Severity::Error, "file_size", Some(offset + 0xc),
+                                                   format!("Record at offset {:#x} is truncated", offset)));
+        }
+        prefetch
+      }));
+    }
+    Ok(())
+  }
+
+  /// Parse a prefetch starting at `offset` of `file`
+  pub fn from_file_at<T : VFile>(file : &mut T, offset : u64, options : &PrefetchParseOptions) -> Result<Prefetch, PrefetchError>
+  {
+    let mut file = OffsetReader::new(file, offset).map_err(PrefetchError::Io)?;
+    Prefetch::from_file_with_options(&mut file, options)
+  }
+
+  pub fn from_file_with_options<T : VFile>(file : &mut T, options : &PrefetchParseOptions) -> Result<Prefetch, PrefetchError>
   {
-    let prefetch_header = PrefetchHeader::from_reader(file)?;
+    Prefetch::parse(file, options).map_err(|err| PrefetchError::from_parse(err, file))
+  }
+
+  fn parse<T : VFile>(file : &mut T, options : &PrefetchParseOptions) -> anyhow::Result<Prefetch>
+  {
+    if options.buffer_stream
+    {
+      return Ok(Prefetch::from_stream(file, options)?)
+    }
+
+    if options.scan_signature
+    {
+      if let Some(offset) = Prefetch::find_header(file, 0, SIGNATURE_SCAN_SIZE as u64)?.filter(|offset| *offset != 0)
+      {
+        let options = PrefetchParseOptions{ scan_signature : false, ..options.clone() };
+        let mut prefetch = Prefetch::from_file_at(file, offset, &options)?;
+        prefetch.warnings.insert(0, ParseWarning::new(Severity::Info, "signature", Some(offset + 4),
+                                                      format!("Prefetch header found at offset {:#x}", offset)));
+        return Ok(prefetch)
+      }
+    }
 
-    let file_information = match prefetch_header.version
+    let compressed = Prefetch::is_compressed(file)?;
+    if compressed
     {
-      PrefetchVersion::WindowsVista => FileInformation::vista_from_reader(file)?,
-      PrefetchVersion::WindowsXP => FileInformation::xp_from_reader(file)?, 
-      PrefetchVersion::Windows8 => FileInformation::w8_from_reader(file)?,
-      //PrefetchVersion::Windows10, windows 10 is compressed in lzxpress ! must handle that case
-      //create an other plugin or first decompress and run this one 
-      _ => return Err(RustructError::Unknown("Unsupported prefetch version".into()).into()),
+      //parsed through a bounded window instead of decompressing the whole file in memory
+      let mut reader = mam::MamReader::new(&mut *file)?;
+      let mut prefetch = Prefetch::from_file_with_options(&mut reader, options)?;
+      if reader.truncated()
+      {
+        let actual = std::io::Seek::seek(&mut reader, SeekFrom::End(0))?;
+        return Err(PrefetchError::DecompressedSizeMismatch{ expected : reader.uncompressed_size(), actual }.into())
+      }
+      prefetch.compressed = true;
+      return Ok(prefetch)
+    }
+
+    let prefetch_header = PrefetchHeader::parse(file, options.assume_version, options.verify_signature)?;
+    let file_information_header = FileInformationHeader::from_reader(file)?;
+
+    let file_information = match file_information_reader(prefetch_header.version)
+    {
+      Some(reader) => reader.read(file)?,
+      None => return Err(PrefetchError::UnsupportedVersion(prefetch_header.version).into()),
     };
 
+    //offsets are checked before seeking, sizes are capped to what remains of the stream
+    let stream_size = file.seek(SeekFrom::End(0))?;
+    if !options.skip_file_list && prefetch_header.first_file_path_offset as u64 > stream_size
+    {
+      return Err(PrefetchError::InvalidOffset{ field : "first_file_path_offset", offset : prefetch_header.first_file_path_offset as u64 }.into())
+    }
 
-    file.seek(SeekFrom::Start(prefetch_header.volume_information_offset as u64))?;
-    let volume_information = VolumeInformation::from_reader(file)?;
- 
-    file.seek(SeekFrom::Start(prefetch_header.first_file_path_offset as u64))?;
-    let files = read_utf16_list(file, prefetch_header.first_file_path_size as usize)?;
+    let mut warnings : Vec<ParseWarning> = Vec::new();
+
+    //the volume information is after the file paths, a file cut in the path list still has its complete paths recovered
+    if prefetch_header.volume_information_offset as u64 > stream_size
+    {
+      warnings.push(ParseWarning::new(Severity::Error, "volume_information_offset", Some(0x6c),
+                    format!("Volume information offset {:#x} is after the end of file, no volumes read", prefetch_header.volume_information_offset)));
+    }
+
+    for (offset, filetime) in &file_information.invalid_execution_times
+    {
+      warnings.push(ParseWarning::new(Severity::Warning, "last_execution_times", Some(*offset),
+                                      format!("Invalid execution time {:#x} skipped", filetime)));
+    }
+
+    if !prefetch_header.signature_is_valid()
+    {
+      warnings.push(ParseWarning::new(Severity::Warning, "signature", Some(0x4),
+                                      format!("Invalid prefetch signature {}, parsed anyway", prefetch_header.signature)));
+    }
+
+    //a wrong size would make the file path list run into the volume information
+    let file_path_size = prefetch_header.file_path_size();
+    if !prefetch_header.file_path_size_valid()
+    {
+      warnings.push(ParseWarning::new(Severity::Warning, "first_file_path_size", Some(0x68),
+                    format!("File path list size {:#x} doesn't match the distance to the volume information, {:#x} bytes read",
+                            prefetch_header.first_file_path_size, file_path_size)));
+    }
+
+    let files = if options.skip_file_list
+    {
+      Vec::new()
+    }
+    else
+    {
+      let available = stream_size - prefetch_header.first_file_path_offset as u64;
+      file.seek(SeekFrom::Start(prefetch_header.first_file_path_offset as u64))?;
+      if file_path_size as u64 > available
+      {
+        let files = read_truncated_utf16_list(file, available as usize)?;
+        warnings.push(ParseWarning::new(Severity::Error, "files", Some(prefetch_header.first_file_path_offset as u64),
+                      format!("File path list is truncated by the end of file, {} file paths recovered", files.len())));
+        files
+      }
+      else
+      {
+        read_utf16_list(file, file_path_size as usize)?
+      }
+    };
     
-    let offset = prefetch_header.volume_information_offset  + volume_information.folder_path_offset;
-    file.seek(SeekFrom::Start(offset as u64))?; 
+    let metrics = match FileMetric::array_from_reader(file, prefetch_header.version, &file_information_header)
+    {
+      Ok(metrics) => metrics,
+      Err(err) =>
+      {
+        warnings.push(ParseWarning::new(Severity::Error, "metrics", Some(file_information_header.metrics_offset as u64),
+                                        format!("Can't read file metrics : {}", err)));
+        Vec::new()
+      },
+    };
+
+    //a corrupted count or size could make the entries and strings overflow the volume information block
+    let volumes_start = (prefetch_header.volume_information_offset as u64).min(stream_size);
+    let mut volumes_end = volumes_start + prefetch_header.volume_information_size as u64;
+    if volumes_end > stream_size
+    {
+      if volumes_start < stream_size
+      {
+        warnings.push(ParseWarning::new(Severity::Error, "volume_information_size", Some(0x74),
+                      format!("Volume information size {:#x} overflow the end of file, {:#x} bytes read",
+                              prefetch_header.volume_information_size, stream_size - volumes_start)));
+      }
+      volumes_end = stream_size;
+    }
+    let entry_size = VolumeInformation::entry_size(prefetch_header.version);
+    let mut volume_count = file_information_header.volume_count as u64;
+    if volume_count * entry_size > volumes_end - volumes_start
+    {
+      let readable = (volumes_end - volumes_start) / entry_size;
+      warnings.push(ParseWarning::new(Severity::Error, "volume_count", Some(0x70),
+                    format!("Volume count {} overflow the volume information block, {} volumes read", volume_count, readable)));
+      volume_count = readable;
+    }
+
+    let mut volumes : Vec<Arc<VolumeInformation>> = Vec::new();
+    for index in 0..volume_count
+    {
+      file.seek(SeekFrom::Start(volumes_start + index * entry_size))?;
+      let mut volume = VolumeInformation::from_reader(file)?;
+      if volume.volume_creation_date.is_none() && volume.volume_creation_filetime != 0
+      {
+        warnings.push(ParseWarning::new(Severity::Warning, "volume_creation_date", Some(volumes_start + index * entry_size + 0x8),
+                      format!("Invalid creation date {:#x} of volume {} skipped", volume.volume_creation_filetime, index)));
+      }
+
+      //structures can be separated by padding, each one is read from its declared offset
+      let device_path_start = volumes_start + volume.volume_path_offset as u64;
+      if device_path_start + volume.volume_path_size as u64 * 2 > volumes_end
+      {
+        warnings.push(ParseWarning::new(Severity::Error, "device_path", Some(device_path_start),
+                      format!("Device path of volume {} overflow the volume information block", index)));
+      }
+      else
+      {
+        file.seek(SeekFrom::Start(device_path_start))?;
+        volume.device_path = read_utf16_field(file, volume.volume_path_size as usize * 2)?;
+      }
+
+      file.seek(SeekFrom::Start(volumes_start + volume.folder_path_offset as u64))?;
+      for _ in 0..volume.folder_path_count
+      {
+        let start = file.stream_position()?;
+        let size = file.read_u16::<Endian>()? as u64;
+        if start + 2 + (size + 1) * 2 > volumes_end
+        {
+          warnings.push(ParseWarning::new(Severity::Error, "directories", Some(start),
+                        format!("Folder path {} of volume {} at offset {:#x} overflow the volume information block, {} folder paths skipped",
+                                volume.directories.len(), index, start, volume.folder_path_count as usize - volume.directories.len())));
+          break;
+        }
+
+        file.seek(SeekFrom::Start(start))?;
+        let decoded = read_sized_utf16(file)?;
+        volume.directories.push(decoded)
+      }
+      volumes.push(Arc::new(volume));
+    }
+
+    let run_count = file_information.number_of_execution();
+    if run_count > options.max_run_count
+    {
+      warnings.push(ParseWarning::new(Severity::Warning, "run_count", Some(file_information.run_count_offset),
+                                      format!("Run count {} is implausibly high, the field may be corrupted", run_count)));
+    }
 
-    let mut volumes : Vec<String> = Vec::new();
-    for _ in 0..volume_information.folder_path_count
+    if options.strict
     {
-      let decoded = read_sized_utf16(file)?;
-      volumes.push(decoded)
+      if let Some(warning) = warnings.iter().find(|warning| warning.severity >= Severity::Warning)
+      {
+        return Err(PrefetchError::Anomaly(warning.message.clone()).into())
+      }
     }
 
-    Ok(Prefetch{  
+    //requested by the caller so it's reported but never an anomaly
+    if let Some(version) = options.assume_version
+    {
+      warnings.insert(0, ParseWarning::new(Severity::Info, "version", Some(0),
+                         format!("Layout of {} forced on a file with version dword {:#x}, values may be wrong",
+                                 version, prefetch_header.version_value)));
+    }
+
+    #[cfg(feature = "research")]
+    let raw =
+    {
+      let mut raw = Vec::new();
+      file.seek(SeekFrom::Start(0))?;
+      file.read_to_end(&mut raw)?;
+      raw
+    };
+
+    let files_description = files_description(&prefetch_header).to_string();
+    let file_references = FileReference::from_metrics(&files, &metrics);
+
+    let mut prefetch = Prefetch{  
      header : Arc::new(prefetch_header),
      file_information : Arc::new(file_information),
-     volume_information : Arc::new(volume_information),
      files,
      volumes,
-    })
+     compressed,
+     warnings,
+     pf_file_created : None,
+     pf_file_modified : None,
+     files_description,
+     hash_is_valid : None,
+     file_information_header : Arc::new(file_information_header),
+     metrics,
+     file_references,
+     #[cfg(feature = "research")]
+     raw,
+    };
+    prefetch.hash_is_valid = prefetch.path_hash_is_valid();
+
+    Ok(prefetch)
   } 
-}
 
-#[derive(Debug,Reflect)]
-pub struct FileInformation
-{
-  last_execution_time : DateTime::<Utc>,
-  number_of_execution : u32,
-}		
+  /// Owned serializable copy of the parsed fields
+  pub fn output(&self) -> PrefetchOutput
+  {
+    PrefetchOutput{
+      executable_name : self.header.file_name().to_string(),
+      version : self.header.version(),
+      file_size : self.header.file_size(),
+      hash : self.header.hash(),
+      run_count : self.file_information.number_of_execution(),
+      last_execution_time : self.file_information.last_execution_time(),
+      last_execution_times : self.file_information.last_execution_times().to_vec(),
+      volume_creation_date : self.volume().and_then(|volume| volume.volume_creation_date()),
+      volume_serial_number : self.volume().map(|volume| volume.volume_serial_number()).unwrap_or_default(),
+      volume_count : self.volume_count(),
+      files : self.files.clone(),
+      file_references : self.file_references.clone(),
+      volumes : self.volumes.iter().map(|volume| volume.as_ref().clone()).collect(),
+      compressed : self.compressed,
+      hash_is_valid : self.hash_is_valid,
+      warnings : self.warnings.clone(),
+      pf_file_created : self.pf_file_created,
+      pf_file_modified : self.pf_file_modified,
+      integrity : self.integrity_report().confidence,
+    }
+  }
 
-impl FileInformation
-{
-  fn vista_from_reader<T : VFile>(file : &mut T) -> anyhow::Result<FileInformation>
+  /// Warnings of severity `min_severity` or higher
+  pub fn warnings_at_least(&self, min_severity : Severity) -> impl Iterator<Item = &ParseWarning>
   {
-    file.seek(SeekFrom::Start(0x80))?;
-    let last_execution_time = file.read_u64::<LittleEndian>()?; 
-    let last_execution_time = WindowsTimestamp(last_execution_time).to_datetime()?;
+    self.warnings.iter().filter(move |warning| warning.severity >= min_severity)
+  }
 
-    file.seek(SeekFrom::Start(0x98))?;
-    let number_of_execution = file.read_u32::<LittleEndian>()?;
+  /// Compact view of the main fields
+  pub fn summary(&self) -> PrefetchSummary
+  {
+    PrefetchSummary{
+      executable_name : self.header.file_name().to_string(),
+      version : self.header.version(),
+      run_count : self.file_information.number_of_execution(),
+      first_execution_time : self.file_information.last_execution_times().last().copied(),
+      last_execution_time : self.file_information.last_execution_time(),
+      file_count : self.referenced_file_count(),
+      referenced_path_bytes : self.total_referenced_path_bytes(),
+      volume_count : self.volume_count(),
+      volume_serial_number : self.volume().map(|volume| volume.volume_serial_number()).unwrap_or_default(),
+    }
+  }
 
-    Ok(FileInformation{
-      last_execution_time, number_of_execution
-    })
+  /// Number of files loaded by the program
+  pub fn referenced_file_count(&self) -> usize
+  {
+    self.files.len()
   }
 
-  fn xp_from_reader<T : VFile>(file : &mut T) -> anyhow::Result<FileInformation>
+  /// Size of all the referenced paths in UTF-16 as stored in the file, without the NUL terminators
+  pub fn total_referenced_path_bytes(&self) -> usize
   {
-    file.seek(SeekFrom::Start(0x78))?;
-    let last_execution_time = file.read_u64::<LittleEndian>()?; 
-    let last_execution_time = WindowsTimestamp(last_execution_time).to_datetime()?;
+    self.files.iter().map(|file| file.encode_utf16().count() * 2).sum()
+  }
 
-    file.seek(SeekFrom::Start(0x90))?;
-    let number_of_execution = file.read_u32::<LittleEndian>()?;
+  /// First volume, usually the one of the executable
+  pub fn volume(&self) -> Option<&VolumeInformation>
+  {
+    self.volumes.first().map(|volume| volume.as_ref())
+  }
 
-    Ok(FileInformation{
-      last_execution_time, number_of_execution
-    })
+  pub fn executable_name(&self) -> &str
+  {
+    self.header.file_name()
   }
 
-  fn w8_from_reader<T : VFile>(file : &mut T) -> anyhow::Result<FileInformation>
+  pub fn run_count(&self) -> u32
   {
-    file.seek(SeekFrom::Start(0x80))?;
-    let last_execution_time = file.read_u64::<LittleEndian>()?; 
-    let last_execution_time = WindowsTimestamp(last_execution_time).to_datetime()?;
+    self.file_information.number_of_execution()
+  }
 
-    file.seek(SeekFrom::Start(0xD0))?;
-    let number_of_execution = file.read_u32::<LittleEndian>()?;
+  /// Serial number of the first volume, 0 when there is no volume
+  pub fn volume_serial(&self) -> u32
+  {
+    self.volume().map(|volume| volume.volume_serial_number()).unwrap_or_default()
+  }
 
-    Ok(FileInformation{
-      last_execution_time, number_of_execution
-    })
+  /// File size stored in the header
+  pub fn file_size(&self) -> u32
+  {
+    self.header.file_size()
   }
 
-  pub fn last_execution_time(&self) -> DateTime::<Utc>
+  /// Directories of every volume
+  pub fn directories(&self) -> impl Iterator<Item = &str>
   {
-    self.last_execution_time
+    self.volumes.iter().flat_map(|volume| volume.directories()).map(|directory| directory.as_str())
   }
- 
-  pub fn number_of_execution(&self) -> u32
+
+  /// Directories of every volume with the volume they were read from
+  pub fn volume_directories(&self) -> impl Iterator<Item = (&VolumeInformation, &str)>
   {
-    self.number_of_execution
+    self.volumes.iter().flat_map(|volume| volume.directories().iter().map(move |directory| (volume.as_ref(), directory.as_str())))
   }
-}
 
+  /// Decode the file path list from `file` one path at a time instead of reading it in `files`, to use with
+  /// `skip_file_list`. `file` is the parsed file, decompressed for compressed prefetch.
+  pub fn files_iter<'a, T : VFile>(&self, file : &'a mut T) -> FilePaths<'a, T>
+  {
+    let offset = self.header.first_file_path_offset as u64;
+    FilePaths{ file, offset, end : offset + self.header.file_path_size() as u64 }
+  }
 
-#[derive(Debug, Reflect)]
-pub struct VolumeInformation
-{
-  #[reflect(skip)]
-  volume_path_offset : u32,
-  #[reflect(skip)]
-  volume_path_size : u32,
-  volume_creation_date: DateTime<Utc>,
-  volume_serial_number : u32,
-  #[reflect(skip)]
-  blob1_offset : u32,
-  #[reflect(skip)]
-  blob1_size : u32,
-  #[reflect(skip)]
-  folder_path_offset : u32,
-  #[reflect(skip)]
-  folder_path_count : u32,
-}
+  /// Index in `volumes` of the volume whose device path prefix `path`
+  pub fn volume_index(&self, path : &str) -> Option<usize>
+  {
+    self.volumes.iter().position(|volume| volume.contains(path))
+  }
 
-impl VolumeInformation
-{
-  pub fn from_reader<T : VFile>(file : &mut T) -> anyhow::Result<VolumeInformation>
+  /// Number of volumes referenced, a program that ran from several volumes (USB drive and system drive) is notable
+  pub fn volume_count(&self) -> u32
   {
-    let volume_path_offset = file.read_u32::<LittleEndian>()?; 
-    let volume_path_size = file.read_u32::<LittleEndian>()?;
-    let volume_creation_date = file.read_u64::<LittleEndian>()?; 
-    let volume_creation_date = WindowsTimestamp(volume_creation_date).to_datetime()?;
-    let volume_serial_number = file.read_u32::<LittleEndian>()?;
-    let blob1_offset = file.read_u32::<LittleEndian>()?;
-    let blob1_size = file.read_u32::<LittleEndian>()?;
-    let folder_path_offset = file.read_u32::<LittleEndian>()?; 
-    let folder_path_count = file.read_u32::<LittleEndian>()?;
+    self.file_information_header.volume_count
+  }
 
-    Ok(VolumeInformation{
-      volume_path_offset, volume_path_size, volume_creation_date, volume_serial_number,
-      blob1_offset, blob1_size,
-      folder_path_offset, folder_path_count,
-    })
+  /// Every execution time stored in the file
+  pub fn all_timestamps(&self) -> Vec<DateTime<Utc>>
+  {
+    self.file_information.last_execution_times().to_vec()
   }
-}
 
-#[derive(Debug, Reflect)]
-pub struct PrefetchHeader
-{
-  #[reflect(skip)]
-  version : PrefetchVersion,  //offset 0
-  #[reflect(skip)]
-  signature : String,        //offset 8
-  file_size : u32,            //offset 0xc
-  file_name : String,         //0x10 + 0x3c/60 ?
-  hash : u32,                 //0x4c ?
+  /// Group the execution times in chronological clusters where each run is at most `window` after the previous one,
+  /// bursts of runs are typical of scripted execution
+  pub fn execution_bursts(&self, window : Duration) -> Vec<Vec<DateTime<Utc>>>
+  {
+    let mut timestamps = self.all_timestamps();
+    timestamps.sort();
 
-  first_file_path_offset : u32, //0x64
+    let mut bursts : Vec<Vec<DateTime<Utc>>> = Vec::new();
+    for timestamp in timestamps
+    {
+      match bursts.last_mut()
+      {
+        Some(burst) if burst.last().is_some_and(|last| timestamp - *last <= window) => burst.push(timestamp),
+        _ => bursts.push(vec![timestamp]),
+      }
+    }
+    bursts
+  }
+
+  /// Every execution time formatted as RFC 3339
+  pub fn execution_times_iso(&self) -> Vec<String>
+  {
+    self.all_timestamps().iter().map(|timestamp| timestamp.to_rfc3339()).collect()
+  }
+
+  /// Raw offsets and counts of the file information block
+  pub fn file_information_header(&self) -> &FileInformationHeader
+  {
+    &self.file_information_header
+  }
+
+  /// Entries of the file metrics array
+  pub fn metrics(&self) -> &[FileMetric]
+  {
+    &self.metrics
+  }
+
+  /// Read a little-endian dword at any offset of the file, to probe undocumented fields
+  #[cfg(feature = "research")]
+  pub fn raw_u32_at(&self, offset : u64) -> anyhow::Result<u32>
+  {
+    let start = usize::try_from(offset)?;
+    match start.checked_add(4).and_then(|end| self.raw.get(start..end))
+    {
+      Some(data) => Ok(Endian::read_u32(data)),
+      None => Err(RustructError::Unknown(format!("Offset {:#x} is out of the file ({} bytes)", offset, self.raw.len())).into()),
+    }
+  }
+
+  /// Size of the data between the end of the last known structure and the file size stored in the header,
+  /// non zero slack can indicate appended data or file reuse
+  pub fn trailing_slack_size(&self) -> u64
+  {
+    (self.header.file_size as u64).saturating_sub(self.file_information_header.sections_end(self.header.version))
+  }
+
+  /// Boot trace or application prefetch, from the executable name of the header
+  pub fn kind(&self) -> PrefetchKind
+  {
+    header_kind(&self.header)
+  }
+
+  /// Executable name used to compare prefetch : NUL padding and surrounding whitespace are trimmed
+  /// and the name is uppercased, as windows does when computing the prefetch file name
+  pub fn canonical_executable_name(&self) -> String
+  {
+    self.header.file_name().trim().to_uppercase()
+  }
+
+  /// Referenced files located on a network share : UNC paths (`\\server\share`) and paths on the
+  /// network redirector devices (`\DEVICE\MUP\`, `\DEVICE\LANMANREDIRECTOR\`, `\DEVICE\WEBDAVREDIRECTOR\`)
+  pub fn network_executions(&self) -> Vec<&str>
+  {
+    self.files.iter().map(|path| path.as_str()).filter(|path| is_network_path(path)).collect()
+  }
+
+  /// At most the first `count` files, the prefetcher store the most important files first
+  pub fn files_preview(&self, count : usize) -> &[String]
+  {
+    &self.files[..count.min(self.files.len())]
+  }
+
+  /// Full path of the executable as stored in the file list
+  pub fn executable_path(&self) -> Option<&str>
+  {
+    //file name is truncated to 29 characters in the header
+    let name = self.canonical_executable_name();
+    if name.is_empty()
+    {
+      return None
+    }
+
+    self.files.iter().map(|path| path.as_str())
+              .find(|path| path.rsplit('\\').next().unwrap_or_default().to_uppercase().starts_with(&name))
+  }
+
+  /// Path of the executable to check if it still exist : `mapping` associate the `\VOLUME{...}` or
+  /// `\DEVICE\HARDDISKVOLUMEn` prefix to a drive letter (case insensitive) giving `C:\WINDOWS\NOTEPAD.EXE`,
+  /// on an unmapped volume the path is relative to the volume root (`\WINDOWS\NOTEPAD.EXE`)
+  /// so the caller only have to prepend its mount point
+  pub fn executable_lookup_path(&self, mapping : Option<&HashMap<String, char>>) -> Option<String>
+  {
+    self.executable_path().map(|path| match map_volume(path, mapping)
+    {
+      (Some(letter), relative) => format!("{}:{}", letter, relative),
+      (None, relative) => relative.to_string(),
+    })
+  }
+
+  /// File list with the volume prefix replaced by a drive letter, `mapping` associate the `\VOLUME{...}` or
+  /// `\DEVICE\HARDDISKVOLUMEn` prefix to the letter (case insensitive), paths on unmapped volumes are left as is
+  pub fn resolved_files(&self, mapping : Option<&HashMap<String, char>>) -> Vec<String>
+  {
+    self.files.iter().map(|path| match map_volume(path, mapping)
+    {
+      (Some(letter), relative) => format!("{}:{}", letter, relative),
+      (None, _) => path.clone(),
+    }).collect()
+  }
+
+  /// Check the header hash against the full device path of the executable,
+  /// every algorithm used by this version is tried (see the `hash` module for the mapping)
+  pub fn verify_hash(&self, path : &str) -> HashVerification
+  {
+    let stored = self.header.hash;
+    let candidates = HashAlgorithm::candidates(self.header.version);
+    let results : Vec<(HashAlgorithm, u32)> = candidates.iter().map(|algorithm| (*algorithm, algorithm.hash(path))).collect();
+
+    let (algorithm, computed) = results.iter().find(|(_, computed)| *computed == stored).unwrap_or(&results[0]);
+    HashVerification{ verified : *computed == stored, algorithm : *algorithm, computed : *computed, stored }
+  }
+
+  /// Result of `verify_hash` on the executable path when it's a device path
+  pub(crate) fn path_hash_is_valid(&self) -> Option<bool>
+  {
+    self.executable_path().filter(|path| path.to_uppercase().starts_with("\\DEVICE\\"))
+                          .map(|path| self.verify_hash(path).verified)
+  }
+
+  /// Compare the header hash with the hash of the .pf file name (`NOTEPAD.EXE-D8414F97.pf`)
+  /// when the executable path couldn't be checked
+  pub fn check_file_name_hash(&mut self, file_name : &str)
+  {
+    if self.hash_is_valid.is_none()
+    {
+      self.hash_is_valid = parse_pf_filename(file_name).map(|(_, hash)| hash == self.header.hash);
+    }
+  }
+
+  /// Offset of the version dword preceding the first SCCA signature found in the `length` bytes after `start`,
+  /// the stream is rewound to its beginning
+  fn find_header<T : VFile>(file : &mut T, start : u64, length : u64) -> anyhow::Result<Option<u64>>
+  {
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(start))?;
+    file.by_ref().take(length).read_to_end(&mut data)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let position = data.windows(SCCA_SIGNATURE.len()).skip(4).position(|window| window == SCCA_SIGNATURE);
+    Ok(position.map(|position| start + position as u64))
+  }
+
+  /// Check for the MAM header of Windows 10 compressed file and rewind the stream
+  fn is_compressed<T : VFile>(file : &mut T) -> anyhow::Result<bool>
+  {
+    let mut magic : [u8; 4] = [0; 4];
+    file.read_exact(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(&magic == MAM_SIGNATURE)
+  }
+}
+
+/**
+ *   Main fields of a prefetch, for dashboards and APIs that don't need the full structure
+ */
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrefetchSummary
+{
+  pub executable_name : String,
+  pub version : PrefetchVersion,
+  pub run_count : u32,
+  /// Oldest of the recorded execution times, the same as `last_execution_time` before Windows 8
+  pub first_execution_time : Option<DateTime<Utc>>,
+  pub last_execution_time : Option<DateTime<Utc>>,
+  pub file_count : usize,
+  pub referenced_path_bytes : usize,
+  pub volume_count : u32,
+  /// Serial number of the first volume
+  pub volume_serial_number : u32,
+}
+
+/**
+ *   Serializable copy of the parsed fields, its JSON schema describe the output for integrators
+ */
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrefetchOutput
+{
+  pub executable_name : String,
+  pub version : PrefetchVersion,
+  pub file_size : u32,
+  pub hash : u32,
+  pub run_count : u32,
+  pub last_execution_time : Option<DateTime<Utc>>,
+  /// Newest first, at most a single value before Windows 8
+  pub last_execution_times : Vec<DateTime<Utc>>,
+  pub volume_creation_date : Option<DateTime<Utc>>,
+  pub volume_serial_number : u32,
+  /// Number of volumes referenced
+  pub volume_count : u32,
+  pub files : Vec<String>,
+  /// Same index as `files`
+  pub file_references : Vec<FileReference>,
+  pub volumes : Vec<VolumeInformation>,
+  pub compressed : bool,
+  pub hash_is_valid : Option<bool>,
+  pub warnings : Vec<ParseWarning>,
+  pub pf_file_created : Option<DateTime<Utc>>,
+  pub pf_file_modified : Option<DateTime<Utc>>,
+  pub integrity : Confidence,
+}
+
+impl PrefetchOutput
+{
+  /// JSON schema of the output
+  pub fn schema() -> schemars::schema::RootSchema
+  {
+    schemars::schema_for!(PrefetchOutput)
+  }
+}
+
+/// Open and parse the prefetch file at `path`
+pub fn parse_prefetch_file<P : AsRef<Path>>(path : P) -> anyhow::Result<Prefetch>
+{
+  parse_prefetch_file_with_options(path, &PrefetchParseOptions::default())
+}
+
+pub fn parse_prefetch_file_with_options<P : AsRef<Path>>(path : P, options : &PrefetchParseOptions) -> anyhow::Result<Prefetch>
+{
+  let file = File::open(&path)?;
+  let mut prefetch = Prefetch::from_file_with_options(&mut BufReader::with_capacity(options.read_buffer_size, file), options)?;
+  if let Some(name) = path.as_ref().file_name()
+  {
+    prefetch.check_file_name_hash(&name.to_string_lossy());
+  }
+  Ok(prefetch)
+}
+
+/**
+ *   Kind of prefetch deduced from the file name
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PrefetchKind
+{
+  /// `NTOSBOOT-B00DFAAD.pf`, the only file written when the prefetcher is configured for boot only
+  BootTrace,
+  /// `<EXECUTABLE>-<HASH>.pf`
+  Application,
+  Unknown,
+}
+
+/// Quick check of the first bytes of a candidate file (at least 16 bytes) : compressed header or
+/// known version and signature, with a plausible file size, without parsing the rest
+pub fn looks_like_prefetch(header_bytes : &[u8]) -> bool
+{
+  //the compressed header is followed by the decompressed size
+  if header_bytes.len() >= 0x8 && header_bytes.starts_with(MAM_SIGNATURE)
+  {
+    let size = Endian::read_u32(&header_bytes[0x4..0x8]) as u64;
+    return (MIN_PREFETCH_SIZE..=MAX_REASONABLE_PREFETCH_SIZE).contains(&size)
+  }
+  if header_bytes.len() < 0x10 || &header_bytes[0x4..0x8] != SCCA_SIGNATURE
+  {
+    return false
+  }
+
+  let file_size = Endian::read_u32(&header_bytes[0xc..0x10]) as u64;
+  PrefetchVersion::from_value(Endian::read_u32(&header_bytes[0x0..0x4])).is_some()
+    && (MIN_PREFETCH_SIZE..=MAX_REASONABLE_PREFETCH_SIZE).contains(&file_size)
+}
+
+/// Executable name and hash of a prefetch file name like `NOTEPAD.EXE-D8414F97.pf`, directories are ignored
+pub fn parse_pf_filename(name : &str) -> Option<(String, u32)>
+{
+  let name = name.rsplit(['\\', '/']).next().unwrap_or(name);
+  if !has_suffix(name, ".pf")
+  {
+    return None
+  }
+  let (executable, hash) = name[..name.len() - 3].rsplit_once('-')?;
+  if executable.is_empty() || hash.len() != 8
+  {
+    return None
+  }
+  Some((executable.to_string(), u32::from_str_radix(hash, 16).ok()?))
+}
+
+/// The boot trace is written with `NTOSBOOT` as executable name, it has no specific timing fields
+/// and is parsed like the other files
+fn header_kind(header : &PrefetchHeader) -> PrefetchKind
+{
+  if header.file_name().eq_ignore_ascii_case("NTOSBOOT")
+  {
+    return PrefetchKind::BootTrace
+  }
+  PrefetchKind::Application
+}
+
+pub(crate) fn files_description(header : &PrefetchHeader) -> &'static str
+{
+  match header_kind(header)
+  {
+    PrefetchKind::BootTrace => "boot-loaded files",
+    _ => "files loaded by the executable",
+  }
+}
+
+/// Tell boot traces from application prefetch using the file name
+pub fn classify_prefetch(name : &str) -> PrefetchKind
+{
+  match parse_pf_filename(name)
+  {
+    Some((executable, _)) if executable.eq_ignore_ascii_case("NTOSBOOT") => PrefetchKind::BootTrace,
+    Some(_) => PrefetchKind::Application,
+    None => PrefetchKind::Unknown,
+  }
+}
+
+/// Path of every `.pf` file of `directory`, sorted
+pub fn list_prefetch_folder<P : AsRef<Path>>(directory : P) -> std::io::Result<Vec<PathBuf>>
+{
+  let mut paths : Vec<PathBuf> = Vec::new();
+  for entry in fs::read_dir(directory)?
+  {
+    let path = entry?.path();
+    if path.is_file() && path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("pf"))
+    {
+      paths.push(path);
+    }
+  }
+  paths.sort();
+  Ok(paths)
+}
+
+/// Decompress a Windows 10 MAM prefetch, the result is an uncompressed prefetch that `Prefetch::from_file` parse
+pub fn decompress_prefetch<R : Read + std::io::Seek>(reader : R) -> anyhow::Result<Vec<u8>>
+{
+  mam::decompress(reader)
+}
+
+/// Parse every `.pf` file of `directory`, results are sorted by path
+pub fn scan_prefetch_folder<P : AsRef<Path>>(directory : P) -> std::io::Result<Vec<(PathBuf, anyhow::Result<Prefetch>)>>
+{
+  scan_prefetch_folder_with_options(directory, &PrefetchParseOptions::default())
+}
+
+pub fn scan_prefetch_folder_with_options<P : AsRef<Path>>(directory : P, options : &PrefetchParseOptions) -> std::io::Result<Vec<(PathBuf, anyhow::Result<Prefetch>)>>
+{
+  Ok(parse_prefetch_files(list_prefetch_folder(directory)?, options))
+}
+
+/// Path of the `.pf` files found under `root` (a mounted image) in the root itself or in any directory
+/// named `Prefetch`, names are matched case-insensitively and symbolic links are not followed
+pub fn list_prefetch_tree<P : AsRef<Path>>(root : P) -> std::io::Result<Vec<PathBuf>>
+{
+  let mut paths = list_prefetch_folder(&root)?;
+  let mut directories : Vec<PathBuf> = vec![root.as_ref().to_path_buf()];
+
+  while let Some(directory) = directories.pop()
+  {
+    let entries = match fs::read_dir(&directory)
+    {
+      Ok(entries) => entries,
+      //unreadable directories of an image (permissions, corruption) shouldn't stop the scan
+      Err(err) => { log::warn!("Can't read directory {}: {}", directory.display(), err); continue },
+    };
+
+    for entry in entries
+    {
+      let entry = match entry
+      {
+        Ok(entry) => entry,
+        Err(err) => { log::warn!("Can't read an entry of directory {}: {}", directory.display(), err); continue },
+      };
+      match entry.file_type()
+      {
+        Ok(file_type) if file_type.is_dir() => (),
+        Ok(_) => continue,
+        Err(err) => { log::warn!("Can't read the type of {}: {}", entry.path().display(), err); continue },
+      }
+
+      let path = entry.path();
+      if entry.file_name().eq_ignore_ascii_case("prefetch")
+      {
+        match list_prefetch_folder(&path)
+        {
+          Ok(files) => paths.extend(files),
+          Err(err) => { log::warn!("Can't read directory {}: {}", path.display(), err); continue },
+        }
+      }
+      directories.push(path);
+    }
+  }
+  paths.sort();
+  paths.dedup();
+  Ok(paths)
+}
+
+/// Recursive version of `scan_prefetch_folder_with_options`, see `list_prefetch_tree`
+pub fn scan_prefetch_tree_with_options<P : AsRef<Path>>(root : P, options : &PrefetchParseOptions) -> std::io::Result<Vec<(PathBuf, anyhow::Result<Prefetch>)>>
+{
+  Ok(parse_prefetch_files(list_prefetch_tree(root)?, options))
+}
+
+/// Parse `paths` keeping their order
+fn parse_prefetch_files(paths : Vec<PathBuf>, options : &PrefetchParseOptions) -> Vec<(PathBuf, anyhow::Result<Prefetch>)>
+{
+  #[cfg(feature = "parallel")]
+  {
+    use rayon::prelude::*;
+    //collect keep the order of the sorted paths
+    paths.into_par_iter().map(|path| { let prefetch = parse_prefetch_file_with_options(&path, options); (path, prefetch) }).collect()
+  }
+  #[cfg(not(feature = "parallel"))]
+  {
+    paths.into_iter().map(|path| { let prefetch = parse_prefetch_file_with_options(&path, options); (path, prefetch) }).collect()
+  }
+}
+
+/// Count for each directory the number of prefetch referencing it, directories referenced by
+/// few programs (like a `\TEMP\` folder) are the unusual locations
+pub fn aggregate_directories(prefetches : &[Prefetch]) -> BTreeMap<String, usize>
+{
+  let mut directories : BTreeMap<String, usize> = BTreeMap::new();
+
+  for prefetch in prefetches
+  {
+    let unique : BTreeSet<&str> = prefetch.directories().collect();
+    for directory in unique
+    {
+      *directories.entry(directory.to_string()).or_insert(0) += 1;
+    }
+  }
+  directories
+}
+
+#[derive(Debug,Reflect)]
+#[cfg_attr(feature = "cache", derive(Serialize, Deserialize))]
+pub struct FileInformation
+{
+  /// Newest of `last_execution_times`, None if no execution time is valid
+  last_execution_time : Option<DateTime::<Utc>>,
+  /// Newest first, Windows 8 and later store the last eight runs, empty and invalid slots are omitted
+  last_execution_times : Vec<DateTime::<Utc>>,
+  number_of_execution : u32,
+  /// Offset and value of the execution times that aren't zero but can't be converted
+  #[reflect(skip)]
+  invalid_execution_times : Vec<(u64, u64)>,
+  /// Offset the run count was read from
+  #[reflect(skip)]
+  run_count_offset : u64,
+}		
+
+/// None for an empty (zero) FILETIME or one Windows can't convert, values from 0x8000000000000000 are rejected
+/// like `FileTimeToSystemTime` does
+fn filetime_to_datetime(filetime : u64) -> Option<DateTime<Utc>>
+{
+  match filetime
+  {
+    0 => None,
+    filetime if filetime >= 1 << 63 => None,
+    filetime => WindowsTimestamp(filetime).to_datetime().ok(),
+  }
+}
+
+impl FileInformation
+{
+  fn vista_from_reader<T : VFile + ?Sized>(file : &mut T) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::execution_times_from_reader(file, 0x80, 1, 0x98)
+  }
+
+  fn xp_from_reader<T : VFile + ?Sized>(file : &mut T) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::execution_times_from_reader(file, 0x78, 1, 0x90)
+  }
+
+  fn w8_from_reader<T : VFile + ?Sized>(file : &mut T) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::execution_times_from_reader(file, 0x80, EXECUTION_TIME_COUNT, 0xD0)
+  }
+
+  /// Windows 10 and 11 (0x1e and 0x1f) use the offsets of Windows 8, only the volume entries are smaller (0x60 bytes
+  /// instead of 0x68) :
+  ///
+  /// | Offset | Size  | Field                                    |
+  /// |--------|-------|------------------------------------------|
+  /// | 0x54   | 4     | metrics array offset                     |
+  /// | 0x58   | 4     | metrics count                            |
+  /// | 0x5c   | 4     | trace chains array offset                |
+  /// | 0x60   | 4     | trace chains count                       |
+  /// | 0x64   | 4     | file paths offset                        |
+  /// | 0x68   | 4     | file paths size                          |
+  /// | 0x6c   | 4     | volume information offset                |
+  /// | 0x70   | 4     | volume count                             |
+  /// | 0x74   | 4     | volume information size                  |
+  /// | 0x80   | 8 x 8 | execution times (FILETIME), newest first |
+  /// | 0xd0   | 4     | run count                                |
+  fn w10_from_reader<T : VFile + ?Sized>(file : &mut T) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::execution_times_from_reader(file, 0x80, EXECUTION_TIME_COUNT, 0xD0)
+  }
+
+  /// `count` execution times at `offset` followed by the run count at `run_count_offset`
+  fn execution_times_from_reader<T : VFile + ?Sized>(file : &mut T, offset : u64, count : usize, run_count_offset : u64) -> anyhow::Result<FileInformation>
+  {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut last_execution_times = Vec::with_capacity(count);
+    let mut invalid_execution_times = Vec::new();
+    for index in 0..count as u64
+    {
+      //slots of the runs that didn't happen yet are zero
+      let filetime = file.read_u64::<Endian>()?;
+      match filetime_to_datetime(filetime)
+      {
+        Some(time) => last_execution_times.push(time),
+        None if filetime != 0 => invalid_execution_times.push((offset + index * 8, filetime)),
+        None => (),
+      }
+    }
+    let last_execution_time = last_execution_times.first().copied();
+
+    file.seek(SeekFrom::Start(run_count_offset))?;
+    let number_of_execution = file.read_u32::<Endian>()?;
+
+    Ok(FileInformation{
+      last_execution_time, last_execution_times, number_of_execution, invalid_execution_times, run_count_offset
+    })
+  }
+
+  pub fn last_execution_time(&self) -> Option<DateTime::<Utc>>
+  {
+    self.last_execution_time
+  }
+
+  /// Every stored execution time, newest first
+  pub fn last_execution_times(&self) -> &[DateTime::<Utc>]
+  {
+    &self.last_execution_times
+  }
+ 
+  pub fn number_of_execution(&self) -> u32
+  {
+    self.number_of_execution
+  }
+}
+
+/**
+ *   Offsets and counts of the structures stored at the start of the file information block
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "cache", derive(Deserialize))]
+pub struct FileInformationHeader
+{
+  pub metrics_offset : u32,           //0x54
+  pub metrics_count : u32,            //0x58
+  pub trace_chains_offset : u32,      //0x5c
+  pub trace_chains_count : u32,       //0x60
+  pub filename_strings_offset : u32,  //0x64
+  pub filename_strings_size : u32,    //0x68
+  pub volumes_information_offset : u32, //0x6c
+  pub volume_count : u32,             //0x70
+  pub volumes_information_size : u32, //0x74
+}
+
+impl FileInformationHeader
+{
+  pub fn from_reader<T : VFile>(file : &mut T) -> anyhow::Result<FileInformationHeader>
+  {
+    file.seek(SeekFrom::Start(0x54))?;
+
+    Ok(FileInformationHeader{
+      metrics_offset : file.read_u32::<Endian>()?,
+      metrics_count : file.read_u32::<Endian>()?,
+      trace_chains_offset : file.read_u32::<Endian>()?,
+      trace_chains_count : file.read_u32::<Endian>()?,
+      filename_strings_offset : file.read_u32::<Endian>()?,
+      filename_strings_size : file.read_u32::<Endian>()?,
+      volumes_information_offset : file.read_u32::<Endian>()?,
+      volume_count : file.read_u32::<Endian>()?,
+      volumes_information_size : file.read_u32::<Endian>()?,
+    })
+  }
+
+  /// Size of a trace chain entry, 12 bytes up to Windows 8 and 8 bytes on Windows 10
+  pub fn trace_chain_entry_size(version : PrefetchVersion) -> u64
+  {
+    match version
+    {
+      PrefetchVersion::WindowsXP | PrefetchVersion::WindowsVista | PrefetchVersion::Windows8 => 12,
+      PrefetchVersion::Windows10 => 8,
+    }
+  }
+
+  /// End of the last section declared by the offsets, counts and sizes of the header
+  pub fn sections_end(&self, version : PrefetchVersion) -> u64
+  {
+    let metrics_end = self.metrics_offset as u64 + self.metrics_count as u64 * FileMetric::entry_size(version);
+    let trace_chains_end = self.trace_chains_offset as u64 + self.trace_chains_count as u64 * FileInformationHeader::trace_chain_entry_size(version);
+    let files_end = self.filename_strings_offset as u64 + self.filename_strings_size as u64;
+    let volumes_end = self.volumes_information_offset as u64 + self.volumes_information_size as u64;
+
+    metrics_end.max(trace_chains_end).max(files_end).max(volumes_end)
+  }
+}
+
+/**
+ *   Entry of the file metrics array, one for each file loaded by the program
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "cache", derive(Deserialize))]
+pub struct FileMetric
+{
+  pub start_time : u32,
+  pub duration : u32,
+  /// Not available on XP
+  pub average_duration : u32,
+  /// Offset of the name from the start of the filename strings, in bytes
+  pub filename_offset : u32,
+  pub filename_length : u32,
+  /// Undocumented, kept as read
+  pub flags : u32,
+  /// NTFS file reference, not available on XP
+  pub file_reference : u64,
+}
+
+impl FileMetric
+{
+  /// Size of an entry, every field is a dword in all versions (the filename length is a count of UTF-16 characters)
+  pub fn entry_size(version : PrefetchVersion) -> u64
+  {
+    match version
+    {
+      PrefetchVersion::WindowsXP => 20,
+      _ => 32,
+    }
+  }
+
+  /// XP store 20 bytes entries without average duration and file reference, later versions 32 bytes entries
+  pub fn from_reader<T : VFile>(file : &mut T, version : PrefetchVersion) -> anyhow::Result<FileMetric>
+  {
+    let start_time = file.read_u32::<Endian>()?;
+    let duration = file.read_u32::<Endian>()?;
+
+    if version == PrefetchVersion::WindowsXP
+    {
+      return Ok(FileMetric{
+        start_time, duration,
+        filename_offset : file.read_u32::<Endian>()?,
+        filename_length : file.read_u32::<Endian>()?,
+        flags : file.read_u32::<Endian>()?,
+        ..Default::default()
+      })
+    }
+
+    Ok(FileMetric{
+      start_time, duration,
+      average_duration : file.read_u32::<Endian>()?,
+      filename_offset : file.read_u32::<Endian>()?,
+      filename_length : file.read_u32::<Endian>()?,
+      flags : file.read_u32::<Endian>()?,
+      file_reference : file.read_u64::<Endian>()?,
+    })
+  }
+
+  pub fn array_from_reader<T : VFile>(file : &mut T, version : PrefetchVersion, header : &FileInformationHeader) -> anyhow::Result<Vec<FileMetric>>
+  {
+    let mut metrics = Vec::new();
+    for index in 0..header.metrics_count as u64
+    {
+      file.seek(SeekFrom::Start(header.metrics_offset as u64 + index * FileMetric::entry_size(version)))?;
+      metrics.push(FileMetric::from_reader(file, version)?);
+    }
+    Ok(metrics)
+  }
+
+  /// MFT entry number, the lower 48 bits of the file reference
+  pub fn mft_entry(&self) -> u64
+  {
+    self.file_reference & 0xffff_ffff_ffff
+  }
+
+  /// MFT sequence number, the upper 16 bits of the file reference
+  pub fn mft_sequence(&self) -> u16
+  {
+    (self.file_reference >> 48) as u16
+  }
+}
+
+/**
+ *   NTFS file reference of a loaded file, zero when the file has no metric entry or on XP
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FileReference
+{
+  pub mft_entry : u64,
+  pub sequence_number : u16,
+}
+
+impl FileReference
+{
+  /// Reference of each of `files` taken from the metric pointing to its name, so indexes match `files`
+  fn from_metrics(files : &[String], metrics : &[FileMetric]) -> Vec<FileReference>
+  {
+    let references : HashMap<u32, FileReference> = metrics.iter().map(|metric|
+      (metric.filename_offset, FileReference{ mft_entry : metric.mft_entry(), sequence_number : metric.mft_sequence() })).collect();
+
+    let mut offset = 0;
+    files.iter().map(|file|
+    {
+      let reference = references.get(&offset).copied().unwrap_or_default();
+      //offsets are in bytes and each name is followed by its UTF-16 NUL
+      offset += (file.encode_utf16().count() as u32 + 1) * 2;
+      reference
+    }).collect()
+  }
+}
+
+/**
+ *   Version specific reader of the file information block
+ */
+pub trait FileInformationReader : Sync
+{
+  fn read(&self, file : &mut dyn VFile) -> anyhow::Result<FileInformation>;
+}
+
+struct XPFileInformationReader;
+
+impl FileInformationReader for XPFileInformationReader
+{
+  fn read(&self, file : &mut dyn VFile) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::xp_from_reader(file)
+  }
+}
+
+struct VistaFileInformationReader;
+
+impl FileInformationReader for VistaFileInformationReader
+{
+  fn read(&self, file : &mut dyn VFile) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::vista_from_reader(file)
+  }
+}
+
+struct W8FileInformationReader;
+
+impl FileInformationReader for W8FileInformationReader
+{
+  fn read(&self, file : &mut dyn VFile) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::w8_from_reader(file)
+  }
+}
+
+struct W10FileInformationReader;
+
+impl FileInformationReader for W10FileInformationReader
+{
+  fn read(&self, file : &mut dyn VFile) -> anyhow::Result<FileInformation>
+  {
+    FileInformation::w10_from_reader(file)
+  }
+}
+
+/// File information reader registered for each supported version
+static FILE_INFORMATION_READERS : [(PrefetchVersion, &dyn FileInformationReader); 4] = [
+  (PrefetchVersion::WindowsXP, &XPFileInformationReader),
+  (PrefetchVersion::WindowsVista, &VistaFileInformationReader),
+  (PrefetchVersion::Windows8, &W8FileInformationReader),
+  (PrefetchVersion::Windows10, &W10FileInformationReader),
+];
+
+/// Return the file information reader of `version` or None if this version is not supported
+pub fn file_information_reader(version : PrefetchVersion) -> Option<&'static dyn FileInformationReader>
+{
+  FILE_INFORMATION_READERS.iter().find(|(reader_version, _)| *reader_version == version).map(|(_, reader)| *reader)
+}
+
+
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize, JsonSchema)]
+pub struct VolumeInformation
+{
+  #[reflect(skip)]
+  #[serde(skip)]
+  volume_path_offset : u32,
+  #[reflect(skip)]
+  #[serde(skip)]
+  volume_path_size : u32,
+  volume_creation_date: Option<DateTime<Utc>>,
+  /// Raw FILETIME of `volume_creation_date`
+  #[reflect(skip)]
+  #[serde(skip)]
+  volume_creation_filetime : u64,
+  volume_serial_number : u32,
+  #[reflect(skip)]
+  #[serde(skip)]
+  blob1_offset : u32,
+  #[reflect(skip)]
+  #[serde(skip)]
+  blob1_size : u32,
+  #[reflect(skip)]
+  #[serde(skip)]
+  folder_path_offset : u32,
+  #[reflect(skip)]
+  #[serde(skip)]
+  folder_path_count : u32,
+  /// `\DEVICE\HARDDISKVOLUME2`, empty if it couldn't be read
+  device_path : String,
+  directories : Vec<String>,
+}
+
+impl VolumeInformation
+{
+  /// Size of an entry of the volume information array, the fields read are at the same offsets in all versions
+  pub fn entry_size(version : PrefetchVersion) -> u64
+  {
+    match version
+    {
+      PrefetchVersion::WindowsXP => 0x28,
+      PrefetchVersion::Windows10 => 0x60,
+      _ => 0x68,
+    }
+  }
+
+  /// Read the entry at the current position, `device_path` and `directories` are read by the caller
+  /// from the offsets of the entry
+  pub fn from_reader<T : VFile>(file : &mut T) -> anyhow::Result<VolumeInformation>
+  {
+    let volume_path_offset = file.read_u32::<Endian>()?; 
+    let volume_path_size = file.read_u32::<Endian>()?;
+    //zero when the creation date is unknown, not a real date
+    let volume_creation_filetime = file.read_u64::<Endian>()?; 
+    let volume_creation_date = filetime_to_datetime(volume_creation_filetime);
+    let volume_serial_number = file.read_u32::<Endian>()?;
+    let blob1_offset = file.read_u32::<Endian>()?;
+    let blob1_size = file.read_u32::<Endian>()?;
+    let folder_path_offset = file.read_u32::<Endian>()?; 
+    let folder_path_count = file.read_u32::<Endian>()?;
+
+    Ok(VolumeInformation{
+      volume_path_offset, volume_path_size, volume_creation_date, volume_creation_filetime, volume_serial_number,
+      blob1_offset, blob1_size,
+      folder_path_offset, folder_path_count,
+      device_path : String::new(), directories : Vec::new(),
+    })
+  }
+
+  pub fn device_path(&self) -> &str
+  {
+    &self.device_path
+  }
+
+  pub fn directories(&self) -> &[String]
+  {
+    &self.directories
+  }
+
+  /// `path` is on this volume, its device path is a prefix of `path` ignoring case
+  pub fn contains(&self, path : &str) -> bool
+  {
+    let device_path = self.device_path.as_str();
+    !device_path.is_empty() && path.len() > device_path.len() && path.is_char_boundary(device_path.len()) &&
+      path[..device_path.len()].eq_ignore_ascii_case(device_path) && path[device_path.len()..].starts_with('\\')
+  }
+
+  pub fn volume_creation_date(&self) -> Option<DateTime<Utc>>
+  {
+    self.volume_creation_date
+  }
+
+  pub fn volume_serial_number(&self) -> u32
+  {
+    self.volume_serial_number
+  }
+
+  /// Serial number as displayed by `vol`, used to group files by disk
+  pub fn volume_serial_hex(&self) -> String
+  {
+    format!("{:08X}", self.volume_serial_number)
+  }
+}
+
+#[derive(Debug, Reflect)]
+#[cfg_attr(feature = "cache", derive(Serialize, Deserialize))]
+pub struct PrefetchHeader
+{
+  #[reflect(skip)]
+  version : PrefetchVersion,  //offset 0
+  /// Raw version dword, distinguish builds sharing a version
+  version_value : u32,
+  /// `PrefetchVersion::name`, reflected in place of the enum
+  version_name : String,
+  signature : String,        //offset 4
+  file_size : u32,            //offset 0xc
+  file_name : String,         //0x10 + 0x3c/60 ?
+  hash : u32,                 //0x4c ?
+
+  first_file_path_offset : u32, //0x64
   first_file_path_size : u32,  //0x68
   volume_information_offset : u32, //0x6c
+  volume_information_size : u32, //0x74
 }
 
-#[derive(Debug)]
-enum PrefetchVersion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum PrefetchVersion
 {
   WindowsXP,
   WindowsVista,
@@ -248,34 +1795,374 @@ enum PrefetchVersion
   Windows10,
 }
 
+impl PrefetchVersion
+{
+  pub fn from_value(value : u32) -> Option<PrefetchVersion>
+  {
+    match value
+    {
+      0x11 => Some(PrefetchVersion::WindowsXP),
+      0x17 => Some(PrefetchVersion::WindowsVista),
+      0x1a => Some(PrefetchVersion::Windows8),
+      //0x1f is written by some Windows 11 builds with the same layout
+      0x1e | 0x1f => Some(PrefetchVersion::Windows10),
+      _ => None,
+    }
+  }
+
+  /// Name of the version, stable across releases of the crate so it can be matched by consumers of the output
+  pub fn name(&self) -> &'static str
+  {
+    match self
+    {
+      PrefetchVersion::WindowsXP => "WindowsXP",
+      PrefetchVersion::WindowsVista => "WindowsVista",
+      PrefetchVersion::Windows8 => "Windows8",
+      PrefetchVersion::Windows10 => "Windows10",
+    }
+  }
+
+  /// Version dword written by this version
+  pub fn value(&self) -> u32
+  {
+    match self
+    {
+      PrefetchVersion::WindowsXP => 0x11,
+      PrefetchVersion::WindowsVista => 0x17,
+      PrefetchVersion::Windows8 => 0x1a,
+      PrefetchVersion::Windows10 => 0x1e,
+    }
+  }
+}
+
+impl fmt::Display for PrefetchVersion
+{
+  fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result
+  {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl FromStr for PrefetchVersion
+{
+  type Err = RustructError;
+
+  /// Parse a windows release name, case-insensitive, releases sharing a format map to the same version
+  fn from_str(name : &str) -> Result<PrefetchVersion, RustructError>
+  {
+    match name.to_ascii_lowercase().as_str()
+    {
+      "windowsxp" => Ok(PrefetchVersion::WindowsXP),
+      "windowsvista" | "windows7" => Ok(PrefetchVersion::WindowsVista),
+      "windows8" => Ok(PrefetchVersion::Windows8),
+      "windows10" | "windows11" => Ok(PrefetchVersion::Windows10),
+      _ => Err(RustructError::Unknown(format!("Unknown prefetch version {}, expected one of WindowsXP, WindowsVista, Windows7, Windows8, Windows10, Windows11", name))),
+    }
+  }
+}
+
+/**
+ *   Format detected from the first bytes of a file, without parsing the body
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefetchFormat
+{
+  Uncompressed(PrefetchVersion),
+  Compressed,
+}
+
+impl PrefetchFormat
+{
+  /// Read only the version dword and the signature (or the MAM header)
+  pub fn from_reader<T : VFile>(file : &mut T) -> anyhow::Result<PrefetchFormat>
+  {
+    let mut magic : [u8; 4] = [0; 4];
+    file.read_exact(&mut magic)?;
+    if &magic == MAM_SIGNATURE
+    {
+      return Ok(PrefetchFormat::Compressed)
+    }
+
+    let value = Endian::read_u32(&magic);
+    let version = PrefetchVersion::from_value(value).ok_or(PrefetchError::UnknownVersion(value))?;
+
+    let mut signature : [u8; 4] = [0; 4];
+    file.read_exact(&mut signature)?;
+    if &signature != SCCA_SIGNATURE
+    {
+      return Err(PrefetchError::BadSignature(signature_string(&signature)).into())
+    }
+
+    Ok(PrefetchFormat::Uncompressed(version))
+  }
+}
+
+impl fmt::Display for PrefetchFormat
+{
+  fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result
+  {
+    match self
+    {
+      PrefetchFormat::Uncompressed(version) => write!(f, "{}", version),
+      PrefetchFormat::Compressed => write!(f, "Windows10 (compressed)"),
+    }
+  }
+}
+
 impl PrefetchHeader
 {
-  pub fn from_reader<T : VFile>(file: &mut T) -> anyhow::Result<PrefetchHeader>
+  /// Read the first 0x78 bytes of the file, nothing after the volume information size, and reject files whose
+  /// signature isn't `SCCA`
+  pub fn from_reader<T : VFile>(file: &mut T) -> Result<PrefetchHeader, PrefetchError>
+  {
+    PrefetchHeader::from_reader_with_version(file, None)
+  }
+
+  /// Use `assume_version` instead of the version matching the version dword
+  pub fn from_reader_with_version<T : VFile>(file: &mut T, assume_version : Option<PrefetchVersion>) -> Result<PrefetchHeader, PrefetchError>
+  {
+    PrefetchHeader::parse(file, assume_version, true).map_err(|err| PrefetchError::from_parse(err, file))
+  }
+
+  /// `verify_signature` return `BadSignature` when the signature isn't `SCCA`, otherwise it's kept as read
+  fn parse<T : VFile>(file: &mut T, assume_version : Option<PrefetchVersion>, verify_signature : bool) -> anyhow::Result<PrefetchHeader>
   {
-    let version = match file.read_u32::<LittleEndian>()?
+    let size = file.seek(SeekFrom::End(0))?;
+    if size < MIN_PREFETCH_SIZE
+    {
+      return Err(PrefetchError::Truncated{ offset : size }.into())
+    }
+    file.seek(SeekFrom::Start(0))?;
+
+    let version_value = file.read_u32::<Endian>()?;
+    let version = match assume_version.or_else(|| PrefetchVersion::from_value(version_value))
     {
-      0x11 => PrefetchVersion::WindowsXP,
-      0x17 => PrefetchVersion::WindowsVista,
-      0x1a => PrefetchVersion::Windows8,
-      0x30 => PrefetchVersion::Windows10,
-      _ => return Err(RustructError::Unknown("Can't match Prefetch version".into()).into()),
+      Some(version) => version,
+      None => return Err(PrefetchError::UnknownVersion(version_value).into()),
     };  
+    let version_name = version.name().to_string();
   
     let mut signature: [u8; 4] = [0; 4];
     file.read_exact(&mut signature)?;
-    let signature = std::str::from_utf8(&signature)?.to_string();
+    if verify_signature && &signature != SCCA_SIGNATURE
+    {
+      return Err(PrefetchError::BadSignature(signature_string(&signature)).into())
+    }
+    let signature = signature_string(&signature);
 
     file.seek(SeekFrom::Current(4))?; //XXX check seek return value
-    let file_size = file.read_u32::<LittleEndian>()?;
-    let file_name = read_utf16_exact(file, 60)?;
-    let hash = file.read_u32::<LittleEndian>()?;
+    let file_size = file.read_u32::<Endian>()?;
+    let file_name = read_utf16_field(file, 60)?;
+    let hash = file.read_u32::<Endian>()?;
 
     file.seek(SeekFrom::Start(0x64))?;
-    let first_file_path_offset = file.read_u32::<LittleEndian>()?;
-    let first_file_path_size = file.read_u32::<LittleEndian>()?;
-    let volume_information_offset = file.read_u32::<LittleEndian>()?;
+    let first_file_path_offset = file.read_u32::<Endian>()?;
+    let first_file_path_size = file.read_u32::<Endian>()?;
+    let volume_information_offset = file.read_u32::<Endian>()?;
+    file.seek(SeekFrom::Current(4))?; //volume count
+    let volume_information_size = file.read_u32::<Endian>()?;
+
+    Ok(PrefetchHeader{version, version_value, version_name, signature, file_size, file_name, hash,
+      first_file_path_offset, first_file_path_size, volume_information_offset, volume_information_size})
+  }
+
+  pub fn version(&self) -> PrefetchVersion
+  {
+    self.version
+  }
+
+  /// Version dword as stored at offset 0
+  pub fn version_value(&self) -> u32
+  {
+    self.version_value
+  }
+
+  pub fn version_name(&self) -> &str
+  {
+    &self.version_name
+  }
+
+  pub fn file_size(&self) -> u32
+  {
+    self.file_size
+  }
+
+  pub fn file_name(&self) -> &str
+  {
+    &self.file_name
+  }
+
+  pub fn signature(&self) -> &str
+  {
+    &self.signature
+  }
+
+  /// False only for headers parsed with `verify_signature` disabled
+  pub fn signature_is_valid(&self) -> bool
+  {
+    self.signature.as_bytes() == SCCA_SIGNATURE
+  }
+
+  pub fn hash(&self) -> u32
+  {
+    self.hash
+  }
+
+  /// Distance between the file path list and the volume information
+  fn file_path_space(&self) -> u32
+  {
+    self.volume_information_offset.saturating_sub(self.first_file_path_offset)
+  }
+
+  /// Size of the file path list that can be read without overlapping the volume information
+  pub fn file_path_size(&self) -> u32
+  {
+    self.first_file_path_size.min(self.file_path_space())
+  }
+
+  /// Before windows 8 the file path list is directly followed by the volume information so the size must match their distance,
+  /// later versions can pad the list
+  pub fn file_path_size_valid(&self) -> bool
+  {
+    match self.version
+    {
+      PrefetchVersion::WindowsXP | PrefetchVersion::WindowsVista => self.first_file_path_size == self.file_path_space(),
+      _ => self.first_file_path_size <= self.file_path_space(),
+    }
+  }
+}
+
+/**
+ *   Iterator over the file path list returned by `Prefetch::files_iter`, a path cut by the end of the list is dropped
+ */
+pub struct FilePaths<'a, T : VFile>
+{
+  file : &'a mut T,
+  /// Start of the next path
+  offset : u64,
+  end : u64,
+}
+
+impl<T : VFile> FilePaths<'_, T>
+{
+  fn read_path(&mut self) -> anyhow::Result<Option<String>>
+  {
+    self.file.seek(SeekFrom::Start(self.offset))?;
+    let mut units : Vec<u16> = Vec::new();
+    while self.offset + 2 <= self.end
+    {
+      let unit = self.file.read_u16::<Endian>()?;
+      self.offset += 2;
+      if unit == 0
+      {
+        return Ok(Some(String::from_utf16_lossy(&units)))
+      }
+      units.push(unit);
+    }
+    self.offset = self.end;
+    Ok(None)
+  }
+}
+
+impl<T : VFile> Iterator for FilePaths<'_, T>
+{
+  type Item = anyhow::Result<String>;
+
+  fn next(&mut self) -> Option<anyhow::Result<String>>
+  {
+    if self.offset >= self.end
+    {
+      return None
+    }
+    match self.read_path()
+    {
+      Ok(path) => path.map(Ok),
+      //stop after an error rather than retrying on the same offset
+      Err(err) => { self.offset = self.end; Some(Err(err)) },
+    }
+  }
+}
+
+/// Read the NUL terminated strings of a list cut by the end of file, the last incomplete string is dropped
+fn read_truncated_utf16_list<T : VFile>(file : &mut T, size : usize) -> anyhow::Result<Vec<String>>
+{
+  let mut data = vec![0; size];
+  file.read_exact(&mut data)?;
+
+  let units : Vec<u16> = data.chunks_exact(2).map(Endian::read_u16).collect();
+  let mut strings : Vec<String> = units.split(|unit| *unit == 0).map(String::from_utf16_lossy).collect();
+  strings.pop();
+  Ok(strings)
+}
+
+/// Read a fixed size string field, the NUL padding and anything after it is dropped
+fn read_utf16_field<T : VFile>(file : &mut T, size : usize) -> anyhow::Result<String>
+{
+  let mut field = read_utf16_exact(file, size)?;
+  if let Some(end) = field.find('\0')
+  {
+    field.truncate(end);
+  }
+  Ok(field)
+}
+
+/// Signature as text if it's printable or as hex so corrupted signature can still be reported
+pub(crate) fn signature_string(signature : &[u8; 4]) -> String
+{
+  if signature.iter().all(|byte| byte.is_ascii_graphic())
+  {
+    signature.iter().map(|byte| *byte as char).collect()
+  }
+  else
+  {
+    signature.iter().map(|byte| format!("{:02x}", byte)).collect()
+  }
+}
 
-    Ok(PrefetchHeader{version, signature, file_size, file_name, hash,
-      first_file_path_offset, first_file_path_size, volume_information_offset})
+/// Remove the `\VOLUME{...}` or `\DEVICE\<name>` prefix of a path
+fn strip_volume_prefix(path : &str) -> &str
+{
+  if has_prefix(path, "\\VOLUME{")
+  {
+    if let Some(end) = path.find('}')
+    {
+      return &path[end + 1..]
+    }
+  }
+  else if has_prefix(path, "\\DEVICE\\")
+  {
+    if let Some(end) = path[8..].find('\\')
+    {
+      return &path[8 + end..]
+    }
   }
+  path
+}
+
+/// Drive letter of the volume prefix of `path` in `mapping` and the path relative to the volume root
+fn map_volume<'a>(path : &'a str, mapping : Option<&HashMap<String, char>>) -> (Option<char>, &'a str)
+{
+  let relative = strip_volume_prefix(path);
+  let prefix = &path[..path.len() - relative.len()];
+  let letter = mapping.and_then(|mapping| mapping.iter().find(|(volume, _)| !prefix.is_empty() && volume.eq_ignore_ascii_case(prefix)));
+  (letter.map(|(_, letter)| *letter), relative)
+}
+
+const NETWORK_PREFIXES : [&str; 4] = ["\\\\", "\\DEVICE\\MUP\\", "\\DEVICE\\LANMANREDIRECTOR\\", "\\DEVICE\\WEBDAVREDIRECTOR\\"];
+
+fn is_network_path(path : &str) -> bool
+{
+  NETWORK_PREFIXES.iter().any(|prefix| has_prefix(path, prefix))
+}
+
+/// Case insensitive prefix check
+fn has_prefix(path : &str, prefix : &str) -> bool
+{
+  path.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+/// Case insensitive suffix check
+fn has_suffix(path : &str, suffix : &str) -> bool
+{
+  path.len().checked_sub(suffix.len()).and_then(|start| path.get(start..)).is_some_and(|end| end.eq_ignore_ascii_case(suffix))
 }
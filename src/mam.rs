@@ -0,0 +1,512 @@
+//! Windows 10 prefetch are stored in a MAM container compressed with LZXPRESS Huffman (MS-XCA 2.2).
+//! `MamReader` decompress the stream on demand and only keep a bounded window of the plaintext in memory,
+//! so the parser can seek into it without having the whole decompressed file resident.
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::ReadBytesExt;
+
+use crate::{Endian, PrefetchError, MAM_SIGNATURE, MAX_REASONABLE_PREFETCH_SIZE};
+
+/// Each Huffman block decode to this number of bytes
+const BLOCK_SIZE : usize = 65536;
+/// Matches can reference data up to 64KiB before the current position
+const HISTORY_SIZE : usize = 65536;
+const SYMBOL_COUNT : usize = 512;
+const TABLE_BITS : u32 = 15;
+/// Size of the compressed input buffer
+const INPUT_BUFFER_SIZE : usize = 8192;
+/// Bytes the bit reader can legitimately read past the end of the compressed data
+const MAX_OVERRUN : usize = 4;
+
+/// Default quantity of decompressed data kept in memory
+pub const DEFAULT_WINDOW_SIZE : usize = 4 * BLOCK_SIZE;
+
+/**
+ *   MAM container header
+ */
+#[derive(Debug)]
+pub struct MamHeader
+{
+  pub uncompressed_size : u32,
+}
+
+impl MamHeader
+{
+  pub fn from_reader<R : Read>(reader : &mut R) -> anyhow::Result<MamHeader>
+  {
+    let mut signature : [u8; 4] = [0; 4];
+    reader.read_exact(&mut signature)?;
+    if &signature != MAM_SIGNATURE
+    {
+      if &signature[0..3] == b"MAM"
+      {
+        return Err(PrefetchError::CompressedUnsupported.into())
+      }
+      return Err(PrefetchError::BadSignature(crate::signature_string(&signature)).into())
+    }
+
+    let uncompressed_size = reader.read_u32::<Endian>()?;
+    Ok(MamHeader{ uncompressed_size })
+  }
+}
+
+/**
+ *   Read + Seek adapter over a MAM compressed stream
+ */
+pub struct MamReader<R : Read + Seek>
+{
+  source : Source<R>,
+  data_start : u64,
+  uncompressed_size : u64,
+  /// End of the decompressed data, smaller than `uncompressed_size` if the compressed stream is truncated
+  size : u64,
+  window : Vec<u8>,
+  window_start : u64,
+  window_size : usize,
+  position : u64,
+  /// Block being decoded, None before the first block and after a restart
+  block : Option<Block>,
+  /// Offset and remaining length of a match cut by the end of a chunk
+  pending_match : (usize, usize),
+}
+
+impl<R : Read + Seek> MamReader<R>
+{
+  /// Read the MAM header at the current position of `inner`
+  pub fn new(inner : R) -> anyhow::Result<MamReader<R>>
+  {
+    MamReader::with_window_size(inner, DEFAULT_WINDOW_SIZE)
+  }
+
+  /// `window_size` is the maximum of decompressed data kept in memory, it can't be less than
+  /// the match history plus one block.
+  /// Fail without decoding anything if the header declare more than `MAX_REASONABLE_PREFETCH_SIZE`.
+  pub fn with_window_size(mut inner : R, window_size : usize) -> anyhow::Result<MamReader<R>>
+  {
+    let header = MamHeader::from_reader(&mut inner)?;
+    let uncompressed_size = header.uncompressed_size as u64;
+    if uncompressed_size > MAX_REASONABLE_PREFETCH_SIZE
+    {
+      return Err(PrefetchError::TooLarge{ size : uncompressed_size }.into())
+    }
+    let data_start = inner.stream_position()?;
+
+    Ok(MamReader{
+      source : Source::new(inner),
+      data_start,
+      uncompressed_size,
+      size : uncompressed_size,
+      window : Vec::new(),
+      window_start : 0,
+      window_size : window_size.max(HISTORY_SIZE + BLOCK_SIZE),
+      position : 0,
+      block : None,
+      pending_match : (0, 0),
+    })
+  }
+
+  /// Uncompressed size stored in the MAM header
+  pub fn uncompressed_size(&self) -> u64
+  {
+    self.uncompressed_size
+  }
+
+  /// Quantity of decompressed data currently kept in memory, at most the window size
+  pub fn window_len(&self) -> usize
+  {
+    self.window.len()
+  }
+
+  /// The compressed data ended before `uncompressed_size` bytes were decoded,
+  /// only known once decoding reached the end of the compressed data
+  pub fn truncated(&self) -> bool
+  {
+    self.size < self.uncompressed_size
+  }
+
+  fn window_end(&self) -> u64
+  {
+    self.window_start + self.window.len() as u64
+  }
+
+  /// Restart decompression from the beginning of the compressed data
+  fn restart(&mut self) -> io::Result<()>
+  {
+    self.source.reset(self.data_start)?;
+    self.window.clear();
+    self.window_start = 0;
+    self.block = None;
+    self.pending_match = (0, 0);
+    Ok(())
+  }
+
+  /// Make `position` available in the window, return false if it's past the end of the data
+  fn fill(&mut self, position : u64) -> io::Result<bool>
+  {
+    if position < self.window_start
+    {
+      self.restart()?;
+    }
+
+    while position >= self.window_end()
+    {
+      if self.window_end() >= self.size
+      {
+        return Ok(false)
+      }
+      self.decode_chunk()?;
+    }
+    Ok(true)
+  }
+
+  /// Drop the start of the window so a new block fit in, keeping enough history for matches
+  fn trim(&mut self)
+  {
+    let limit = self.window_size - BLOCK_SIZE;
+    if self.window.len() > limit
+    {
+      let excess = (self.window.len() - limit).min(self.window.len() - HISTORY_SIZE);
+      self.window.drain(..excess);
+      self.window_start += excess as u64;
+    }
+  }
+
+  /// Decode up to a block of data at the end of the window, a match longer than the remaining space
+  /// is completed by the next call so the window never grow past `window_size`
+  fn decode_chunk(&mut self) -> io::Result<()>
+  {
+    self.trim();
+    let chunk_end = self.window_end() + BLOCK_SIZE as u64;
+
+    while self.window_end() < chunk_end.min(self.size)
+    {
+      let (offset, remaining) = self.pending_match;
+      if remaining > 0
+      {
+        let count = remaining.min((chunk_end - self.window_end()) as usize);
+        for _ in 0..count
+        {
+          let byte = self.window[self.window.len() - offset];
+          self.window.push(byte);
+        }
+        self.pending_match = (offset, remaining - count);
+        continue;
+      }
+
+      let mut block = match self.block.take()
+      {
+        Some(block) if self.window_end() < block.end => block,
+        _ => match self.read_block()?
+        {
+          Some(block) => block,
+          None => { self.size = self.window_end(); return Ok(()) },
+        },
+      };
+      let decoded = self.decode_symbols(&mut block, chunk_end);
+      self.block = Some(block);
+      decoded?;
+    }
+
+    Ok(())
+  }
+
+  /// Read the Huffman table starting a new block, None if the compressed data ended
+  fn read_block(&mut self) -> io::Result<Option<Block>>
+  {
+    let mut packed_lengths : [u8; SYMBOL_COUNT / 2] = [0; SYMBOL_COUNT / 2];
+    self.source.read_exact(&mut packed_lengths);
+    if self.source.overrun > 0
+    {
+      return Ok(None)
+    }
+
+    let mut lengths : [u8; SYMBOL_COUNT] = [0; SYMBOL_COUNT];
+    for (index, packed) in packed_lengths.iter().enumerate()
+    {
+      lengths[index * 2] = packed & 0xf;
+      lengths[index * 2 + 1] = packed >> 4;
+    }
+    let table = build_table(&lengths)?;
+    let bits = BitReader::new(&mut self.source);
+
+    Ok(Some(Block{ table, lengths, bits, end : self.window_end() + BLOCK_SIZE as u64 }))
+  }
+
+  /// Decode literals of `block` up to `chunk_end` or the end of the block, stop at the first match
+  /// and leave it in `pending_match`
+  fn decode_symbols(&mut self, block : &mut Block, chunk_end : u64) -> io::Result<()>
+  {
+    let end = chunk_end.min(block.end).min(self.size);
+
+    while self.window_end() < end
+    {
+      if self.source.overrun > MAX_OVERRUN
+      {
+        self.size = self.window_end();
+        break;
+      }
+
+      let symbol = block.table[block.bits.peek() as usize] as usize;
+      block.bits.consume(block.lengths[symbol] as u32, &mut self.source);
+
+      if symbol < 256
+      {
+        self.window.push(symbol as u8);
+        continue;
+      }
+
+      let symbol = symbol - 256;
+      let mut length = symbol & 0xf;
+      let offset_bits = (symbol >> 4) as u32;
+
+      if length == 15
+      {
+        length = self.source.read_u8() as usize;
+        if length == 255
+        {
+          length = self.source.read_u16() as usize;
+          if length == 0
+          {
+            length = self.source.read_u32() as usize;
+          }
+          if length < 15
+          {
+            return Err(invalid_data("Invalid LZXPRESS match length"))
+          }
+          length -= 15;
+        }
+        length += 15;
+      }
+      length += 3;
+
+      let offset = (block.bits.read(offset_bits, &mut self.source) + (1 << offset_bits)) as usize;
+      if offset > self.window.len()
+      {
+        return Err(invalid_data("LZXPRESS match offset points before the start of the data"))
+      }
+
+      self.pending_match = (offset, length.min((self.size - self.window_end()) as usize));
+      break;
+    }
+
+    Ok(())
+  }
+}
+
+/**
+ *   Huffman block being decoded, a block is decoded over several chunks when matches cross the end of the window
+ */
+struct Block
+{
+  table : Vec<u16>,
+  lengths : [u8; SYMBOL_COUNT],
+  bits : BitReader,
+  /// Window position where the next block start
+  end : u64,
+}
+
+/// Decompress a whole MAM stream, fail if the data doesn't match the size stored in the header
+pub fn decompress<R : Read + Seek>(inner : R) -> anyhow::Result<Vec<u8>>
+{
+  let mut reader = MamReader::new(inner)?;
+  let mut data = Vec::new();
+  reader.read_to_end(&mut data)?;
+
+  let expected = reader.uncompressed_size();
+  if data.len() as u64 != expected
+  {
+    return Err(PrefetchError::DecompressedSizeMismatch{ expected, actual : data.len() as u64 }.into())
+  }
+  Ok(data)
+}
+
+impl<R : Read + Seek> Read for MamReader<R>
+{
+  fn read(&mut self, buf : &mut [u8]) -> io::Result<usize>
+  {
+    if buf.is_empty() || !self.fill(self.position)?
+    {
+      return Ok(0)
+    }
+
+    let offset = (self.position - self.window_start) as usize;
+    let count = buf.len().min(self.window.len() - offset);
+    buf[..count].copy_from_slice(&self.window[offset..offset + count]);
+    self.position += count as u64;
+
+    Ok(count)
+  }
+}
+
+impl<R : Read + Seek> Seek for MamReader<R>
+{
+  fn seek(&mut self, pos : SeekFrom) -> io::Result<u64>
+  {
+    let position = match pos
+    {
+      SeekFrom::Start(offset) => offset as i128,
+      SeekFrom::End(offset) => self.size as i128 + offset as i128,
+      SeekFrom::Current(offset) => self.position as i128 + offset as i128,
+    };
+
+    if position < 0
+    {
+      return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative position"))
+    }
+    self.position = position as u64;
+
+    Ok(self.position)
+  }
+}
+
+fn invalid_data(message : &str) -> io::Error
+{
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Build the decoding table indexed by the next 15 bits of the stream
+fn build_table(lengths : &[u8; SYMBOL_COUNT]) -> io::Result<Vec<u16>>
+{
+  let mut table : Vec<u16> = Vec::with_capacity(1 << TABLE_BITS);
+
+  for bit_length in 1..=TABLE_BITS as u8
+  {
+    for (symbol, length) in lengths.iter().enumerate()
+    {
+      if *length == bit_length
+      {
+        let count = 1 << (TABLE_BITS - bit_length as u32);
+        if table.len() + count > 1 << TABLE_BITS
+        {
+          return Err(invalid_data("Invalid LZXPRESS Huffman table"))
+        }
+        table.extend(std::iter::repeat(symbol as u16).take(count));
+      }
+    }
+  }
+
+  if table.len() != 1 << TABLE_BITS
+  {
+    return Err(invalid_data("Incomplete LZXPRESS Huffman table"))
+  }
+  Ok(table)
+}
+
+/**
+ *   Sequential reader over the compressed data, return zero past the end and count the overrun
+ */
+struct Source<R : Read + Seek>
+{
+  inner : R,
+  buffer : Vec<u8>,
+  offset : usize,
+  length : usize,
+  overrun : usize,
+}
+
+impl<R : Read + Seek> Source<R>
+{
+  fn new(inner : R) -> Source<R>
+  {
+    Source{ inner, buffer : vec![0; INPUT_BUFFER_SIZE], offset : 0, length : 0, overrun : 0 }
+  }
+
+  fn reset(&mut self, position : u64) -> io::Result<()>
+  {
+    self.inner.seek(SeekFrom::Start(position))?;
+    self.offset = 0;
+    self.length = 0;
+    self.overrun = 0;
+    Ok(())
+  }
+
+  fn read_u8(&mut self) -> u8
+  {
+    if self.offset == self.length
+    {
+      self.length = loop
+      {
+        match self.inner.read(&mut self.buffer)
+        {
+          Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+          result => break result.unwrap_or(0),
+        }
+      };
+      self.offset = 0;
+
+      if self.length == 0
+      {
+        self.overrun += 1;
+        return 0
+      }
+    }
+
+    let byte = self.buffer[self.offset];
+    self.offset += 1;
+    byte
+  }
+
+  fn read_u16(&mut self) -> u16
+  {
+    u16::from_le_bytes([self.read_u8(), self.read_u8()])
+  }
+
+  fn read_u32(&mut self) -> u32
+  {
+    u32::from_le_bytes([self.read_u8(), self.read_u8(), self.read_u8(), self.read_u8()])
+  }
+
+  fn read_exact(&mut self, buf : &mut [u8])
+  {
+    for byte in buf.iter_mut()
+    {
+      *byte = self.read_u8();
+    }
+  }
+}
+
+/**
+ *   Bit stream made of 16 bits little-endian words read most significant bit first
+ */
+struct BitReader
+{
+  next : u32,
+  extra : i32,
+}
+
+impl BitReader
+{
+  fn new<R : Read + Seek>(source : &mut Source<R>) -> BitReader
+  {
+    let next = ((source.read_u16() as u32) << 16) | source.read_u16() as u32;
+    BitReader{ next, extra : 16 }
+  }
+
+  fn peek(&self) -> u32
+  {
+    self.next >> (32 - TABLE_BITS)
+  }
+
+  fn consume<R : Read + Seek>(&mut self, count : u32, source : &mut Source<R>)
+  {
+    self.next <<= count;
+    self.extra -= count as i32;
+    if self.extra < 0
+    {
+      self.next |= (source.read_u16() as u32) << (-self.extra) as u32;
+      self.extra += 16;
+    }
+  }
+
+  fn read<R : Read + Seek>(&mut self, count : u32, source : &mut Source<R>) -> u32
+  {
+    if count == 0
+    {
+      return 0
+    }
+    let value = self.next >> (32 - count);
+    self.consume(count, source);
+    value
+  }
+}
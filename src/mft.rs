@@ -0,0 +1,52 @@
+//! Correlation of the NTFS file references of the file metrics with a `$MFT` parsed by the caller,
+//! to find files that were deleted or renamed since they were loaded.
+use serde::Serialize;
+
+use crate::{FileReference, Prefetch};
+
+/**
+ *   Lookup of an MFT entry, implemented by the caller on top of its `$MFT` parser
+ */
+pub trait MftResolver
+{
+  /// Current name of the file at `entry` if the entry is in use with sequence number `sequence`
+  fn resolve(&self, entry : u64, sequence : u16) -> Option<String>;
+}
+
+/**
+ *   Result of the resolution of the file reference of a metric
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCorrelation
+{
+  /// Path recorded in the prefetch for this reference
+  pub path : Option<String>,
+  pub mft_entry : u64,
+  pub mft_sequence : u16,
+  /// Name the reference resolve to now, None if the file was deleted or the entry reused
+  pub resolved_name : Option<String>,
+  /// The reference resolve to a name different from the recorded one
+  pub renamed : bool,
+}
+
+impl Prefetch
+{
+  /// Resolve the file reference of every file having one (XP doesn't store them), the reference of a file
+  /// is taken from the metric pointing to its name as metrics aren't always in the order of the paths
+  pub fn correlate_files(&self, resolver : &dyn MftResolver) -> Vec<FileCorrelation>
+  {
+    let referenced = self.files.iter().zip(&self.file_references).filter(|(_, reference)| **reference != FileReference::default());
+    referenced.map(|(path, reference)|
+    {
+      let path = Some(path.clone());
+      let resolved_name = resolver.resolve(reference.mft_entry, reference.sequence_number);
+      let renamed = match (&path, &resolved_name)
+      {
+        (Some(path), Some(name)) => !path.rsplit('\\').next().unwrap_or_default().eq_ignore_ascii_case(name.rsplit('\\').next().unwrap_or_default()),
+        _ => false,
+      };
+
+      FileCorrelation{ path, mft_entry : reference.mft_entry, mft_sequence : reference.sequence_number, resolved_name, renamed }
+    }).collect()
+  }
+}
@@ -0,0 +1,55 @@
+//! Reader exposing a stream from a given offset, used to parse prefetch embedded in a larger file
+//! (carved data, containers) with the absolute offsets of the format.
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+
+/**
+ *   Read + Seek adapter where position 0 is `base` in the inner stream
+ */
+pub struct OffsetReader<R : Read + Seek>
+{
+  inner : R,
+  base : u64,
+}
+
+impl<R : Read + Seek> OffsetReader<R>
+{
+  pub fn new(mut inner : R, base : u64) -> io::Result<OffsetReader<R>>
+  {
+    inner.seek(SeekFrom::Start(base))?;
+    Ok(OffsetReader{ inner, base })
+  }
+
+  /// Offset of the start of the data in the inner stream
+  pub fn base(&self) -> u64
+  {
+    self.base
+  }
+}
+
+impl<R : Read + Seek> Read for OffsetReader<R>
+{
+  fn read(&mut self, buf : &mut [u8]) -> io::Result<usize>
+  {
+    self.inner.read(buf)
+  }
+}
+
+impl<R : Read + Seek> Seek for OffsetReader<R>
+{
+  fn seek(&mut self, pos : SeekFrom) -> io::Result<u64>
+  {
+    let position = match pos
+    {
+      SeekFrom::Start(offset) => self.inner.seek(SeekFrom::Start(self.base + offset))?,
+      _ => self.inner.seek(pos)?,
+    };
+
+    if position < self.base
+    {
+      self.inner.seek(SeekFrom::Start(self.base))?;
+      return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek before the start of the data"))
+    }
+    Ok(position - self.base)
+  }
+}
@@ -0,0 +1,63 @@
+//! ReadyBoot boot trace (`Prefetch\ReadyBoot\*.fx`), these files list what was read during boot
+//! like application prefetch but their layout is not documented, so the file list is recovered
+//! by scanning the trace for NUL terminated UTF-16 paths.
+use std::collections::HashSet;
+
+use tap::vfile::VFile;
+
+/// Shortest string considered as a path
+const MIN_PATH_LENGTH : usize = 4;
+
+/**
+ *   Paths found by scanning a ReadyBoot trace, the structure of the trace isn't parsed so this is a heuristic :
+ *   strings that look like paths but aren't file names can be reported and paths stored differently are missed
+ */
+#[derive(Debug, Default)]
+pub struct ReadyBootTrace
+{
+  /// Strings that look like absolute paths, in their order of appearance in the trace, without duplicates
+  pub candidate_paths : Vec<String>,
+}
+
+impl ReadyBootTrace
+{
+  /// Heuristic scan of the whole trace for NUL terminated UTF-16 strings starting with `\` and with at least two separators
+  pub fn scan_paths<T : VFile>(file : &mut T) -> anyhow::Result<ReadyBootTrace>
+  {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+
+    let mut seen = HashSet::new();
+    let mut candidate_paths = Vec::new();
+    //strings are not always aligned on two bytes
+    for alignment in 0..2
+    {
+      let units : Vec<u16> = data[alignment.min(data.len())..].chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+      for path in units.split(|unit| *unit == 0).filter_map(decode_path)
+      {
+        if seen.insert(path.clone())
+        {
+          candidate_paths.push(path);
+        }
+      }
+    }
+
+    Ok(ReadyBootTrace{ candidate_paths })
+  }
+}
+
+/// Decode `units` if it look like an absolute path
+fn decode_path(units : &[u16]) -> Option<String>
+{
+  if units.len() < MIN_PATH_LENGTH || units[0] != '\\' as u16
+  {
+    return None
+  }
+
+  let path = String::from_utf16(units).ok()?;
+  if path.chars().all(|c| !c.is_control()) && path.matches('\\').count() >= 2
+  {
+    return Some(path)
+  }
+  None
+}
@@ -0,0 +1,141 @@
+//! Sources of prefetch files for batch parsing, so files can be read from a directory or directly
+//! from a container (zip, evidence file) without being extracted first.
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use tap::vfile::VFile;
+
+use crate::{Prefetch, PrefetchParseOptions, DEFAULT_READ_BUFFER_SIZE, MAX_REASONABLE_PREFETCH_SIZE};
+
+/**
+ *   Set of named files that can be opened for parsing
+ */
+pub trait PrefetchSource
+{
+  /// Name and content of every file of the source
+  fn entries(&mut self) -> anyhow::Result<Box<dyn Iterator<Item = (String, Box<dyn VFile>)> + '_>>;
+}
+
+/**
+ *   Files of a directory, not recursive
+ */
+pub struct DirectorySource
+{
+  directory : PathBuf,
+}
+
+impl DirectorySource
+{
+  pub fn new<P : Into<PathBuf>>(directory : P) -> DirectorySource
+  {
+    DirectorySource{ directory : directory.into() }
+  }
+}
+
+impl PrefetchSource for DirectorySource
+{
+  fn entries(&mut self) -> anyhow::Result<Box<dyn Iterator<Item = (String, Box<dyn VFile>)> + '_>>
+  {
+    let entries = fs::read_dir(&self.directory)?.filter_map(|entry|
+    {
+      let path = entry.ok()?.path();
+      if !path.is_file()
+      {
+        return None
+      }
+      match File::open(&path)
+      {
+        Ok(file) => Some((path.display().to_string(), Box::new(BufReader::with_capacity(DEFAULT_READ_BUFFER_SIZE, file)) as Box<dyn VFile>)),
+        Err(err) => { log::warn!("Can't open {}: {}", path.display(), err); None },
+      }
+    });
+    Ok(Box::new(entries))
+  }
+}
+
+/**
+ *   `.pf` files of a zip archive, each entry is decompressed in memory as zip entries can't seek,
+ *   entries larger than `MAX_REASONABLE_PREFETCH_SIZE` are skipped
+ */
+#[cfg(feature = "zip")]
+pub struct ZipSource<R : std::io::Read + std::io::Seek>
+{
+  archive : zip::ZipArchive<R>,
+}
+
+#[cfg(feature = "zip")]
+impl<R : std::io::Read + std::io::Seek> ZipSource<R>
+{
+  pub fn new(reader : R) -> anyhow::Result<ZipSource<R>>
+  {
+    Ok(ZipSource{ archive : zip::ZipArchive::new(reader)? })
+  }
+}
+
+#[cfg(feature = "zip")]
+impl<R : std::io::Read + std::io::Seek> PrefetchSource for ZipSource<R>
+{
+  fn entries(&mut self) -> anyhow::Result<Box<dyn Iterator<Item = (String, Box<dyn VFile>)> + '_>>
+  {
+    use std::io::Read;
+
+    let archive = &mut self.archive;
+    let entries = (0..archive.len()).filter_map(move |index|
+    {
+      let mut entry = match archive.by_index(index)
+      {
+        Ok(entry) => entry,
+        Err(err) => { log::warn!("Can't read zip entry {}: {}", index, err); return None },
+      };
+      if entry.is_dir() || !is_prefetch_name(entry.name())
+      {
+        return None
+      }
+      if entry.size() > MAX_REASONABLE_PREFETCH_SIZE
+      {
+        log::warn!("Zip entry {} of {} bytes is too large to be a prefetch", entry.name(), entry.size());
+        return None
+      }
+
+      //the declared size can be wrong, never decompress more than the cap
+      let mut data = Vec::with_capacity(entry.size() as usize);
+      let read = (&mut entry).take(MAX_REASONABLE_PREFETCH_SIZE + 1).read_to_end(&mut data);
+      if let Err(err) = read
+      {
+        log::warn!("Can't decompress zip entry {}: {}", entry.name(), err);
+        return None
+      }
+      if data.len() as u64 > MAX_REASONABLE_PREFETCH_SIZE
+      {
+        log::warn!("Zip entry {} decompress to more than {} bytes", entry.name(), MAX_REASONABLE_PREFETCH_SIZE);
+        return None
+      }
+      Some((entry.name().to_string(), Box::new(std::io::Cursor::new(data)) as Box<dyn VFile>))
+    });
+    Ok(Box::new(entries))
+  }
+}
+
+/// Names ending with `.pf`, whatever their case
+fn is_prefetch_name(name : &str) -> bool
+{
+  name.to_ascii_lowercase().ends_with(".pf")
+}
+
+/// Parse every `.pf` file of `source`, results are sorted by name
+pub fn scan_prefetch_source(source : &mut dyn PrefetchSource) -> anyhow::Result<Vec<(String, anyhow::Result<Prefetch>)>>
+{
+  let mut results : Vec<(String, anyhow::Result<Prefetch>)> = source.entries()?
+    .filter(|(name, _)| is_prefetch_name(name))
+    .map(|(name, mut file)|
+    {
+      let prefetch = Prefetch::from_named_file(&mut file, &name, &PrefetchParseOptions::default());
+      (name, prefetch.map_err(anyhow::Error::from))
+    })
+    .collect();
+  results.sort_by(|left, right| left.0.cmp(&right.0));
+
+  Ok(results)
+}
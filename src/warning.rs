@@ -0,0 +1,82 @@
+//! Non fatal problems found while parsing, tagged with a severity and the field they concern so
+//! tools can filter them without matching the messages.
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use tap::error::RustructError;
+
+/**
+ *   Impact of a warning on the parsed values
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity
+{
+  /// Parsing was adapted, values are reliable
+  Info,
+  /// A value is suspicious but the structures are consistent
+  Warning,
+  /// Part of the data is missing or couldn't be read
+  Error,
+}
+
+impl fmt::Display for Severity
+{
+  fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result
+  {
+    match self
+    {
+      Severity::Info => write!(f, "info"),
+      Severity::Warning => write!(f, "warning"),
+      Severity::Error => write!(f, "error"),
+    }
+  }
+}
+
+impl FromStr for Severity
+{
+  type Err = RustructError;
+
+  fn from_str(name : &str) -> Result<Severity, RustructError>
+  {
+    match name.to_ascii_lowercase().as_str()
+    {
+      "info" => Ok(Severity::Info),
+      "warning" => Ok(Severity::Warning),
+      "error" => Ok(Severity::Error),
+      _ => Err(RustructError::Unknown(format!("Unknown severity {}", name))),
+    }
+  }
+}
+
+/**
+ *   Warning of `Prefetch::warnings`
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ParseWarning
+{
+  pub severity : Severity,
+  /// Name of the field or structure concerned
+  pub field : String,
+  /// Offset in the file of the data concerned, when it's known
+  pub offset : Option<u64>,
+  pub message : String,
+}
+
+impl ParseWarning
+{
+  pub fn new<S : Into<String>>(severity : Severity, field : &str, offset : Option<u64>, message : S) -> ParseWarning
+  {
+    ParseWarning{ severity, field : field.to_string(), offset, message : message.into() }
+  }
+}
+
+impl fmt::Display for ParseWarning
+{
+  fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result
+  {
+    write!(f, "{}", self.message)
+  }
+}
@@ -0,0 +1,147 @@
+//! Serialize a parsed prefetch back to an uncompressed prefetch file, to build fixtures and for format research.
+//! Offsets are recomputed, so only the parsed content is written back (unknown fields and slack are lost).
+use byteorder::{ByteOrder, WriteBytesExt};
+use chrono::{DateTime, Utc};
+
+use crate::{Endian, Prefetch, PrefetchVersion, FileMetric, EXECUTION_TIME_COUNT};
+
+/// Seconds between 1601-01-01 and 1970-01-01
+const FILETIME_UNIX_DIFFERENCE : i64 = 11_644_473_600;
+
+/// Offsets of a version layout
+struct Layout
+{
+  version_value : u32,
+  execution_time_offset : usize,
+  execution_time_count : usize,
+  run_count_offset : usize,
+  metrics_offset : usize,
+  volume_entry_size : usize,
+}
+
+impl Layout
+{
+  fn new(version : PrefetchVersion) -> Layout
+  {
+    let (version_value, execution_time_offset, execution_time_count, run_count_offset, metrics_offset, volume_entry_size) = match version
+    {
+      PrefetchVersion::WindowsXP => (0x11, 0x78, 1, 0x90, 0x98, 0x28),
+      PrefetchVersion::WindowsVista => (0x17, 0x80, 1, 0x98, 0xf0, 0x68),
+      PrefetchVersion::Windows8 => (0x1a, 0x80, EXECUTION_TIME_COUNT, 0xd0, 0x130, 0x68),
+      PrefetchVersion::Windows10 => (0x1e, 0x80, EXECUTION_TIME_COUNT, 0xd0, 0x130, 0x60),
+    };
+
+    Layout{ version_value, execution_time_offset, execution_time_count, run_count_offset, metrics_offset, volume_entry_size }
+  }
+}
+
+impl Prefetch
+{
+  /// Write an uncompressed prefetch file, `Prefetch::from_file` on the result return an equivalent struct
+  pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>>
+  {
+    let version = self.header.version();
+    let layout = Layout::new(version);
+
+    let mut data : Vec<u8> = vec![0; layout.metrics_offset];
+    for metric in &self.metrics
+    {
+      write_metric(&mut data, metric, version)?;
+    }
+
+    let filename_strings_offset = data.len();
+    for file in &self.files
+    {
+      write_utf16(&mut data, file)?;
+      data.write_u16::<Endian>(0)?;
+    }
+    let filename_strings_size = data.len() - filename_strings_offset;
+
+    //entries are followed by the strings of each volume, offsets are relative to the start of the entries
+    let volumes_offset = data.len();
+    data.resize(volumes_offset + self.volumes.len() * layout.volume_entry_size, 0);
+    for (index, volume) in self.volumes.iter().enumerate()
+    {
+      let device_path_offset = data.len() - volumes_offset;
+      write_utf16(&mut data, volume.device_path())?;
+      data.write_u16::<Endian>(0)?;
+
+      let directories_offset = data.len() - volumes_offset;
+      for directory in volume.directories()
+      {
+        data.write_u16::<Endian>(directory.encode_utf16().count() as u16)?;
+        write_utf16(&mut data, directory)?;
+        data.write_u16::<Endian>(0)?;
+      }
+
+      let entry = &mut data[volumes_offset + index * layout.volume_entry_size..];
+      Endian::write_u32(&mut entry[0x0..], device_path_offset as u32);
+      Endian::write_u32(&mut entry[0x4..], volume.device_path().encode_utf16().count() as u32);
+      Endian::write_u64(&mut entry[0x8..], volume.volume_creation_date().map(to_filetime).unwrap_or(0));
+      Endian::write_u32(&mut entry[0x10..], volume.volume_serial_number());
+      Endian::write_u32(&mut entry[0x1c..], directories_offset as u32);
+      Endian::write_u32(&mut entry[0x20..], volume.directories().len() as u32);
+    }
+    let volumes_size = data.len() - volumes_offset;
+
+    let file_size = data.len() as u32;
+    Endian::write_u32(&mut data[0x0..], layout.version_value);
+    data[0x4..0x8].copy_from_slice(crate::SCCA_SIGNATURE);
+    Endian::write_u32(&mut data[0xc..], file_size);
+    //executable name is a 60 bytes field including the terminating NUL
+    for (index, unit) in self.header.file_name().encode_utf16().take(29).enumerate()
+    {
+      Endian::write_u16(&mut data[0x10 + index * 2..], unit);
+    }
+    Endian::write_u32(&mut data[0x4c..], self.header.hash());
+
+    Endian::write_u32(&mut data[0x54..], layout.metrics_offset as u32);
+    Endian::write_u32(&mut data[0x58..], self.metrics.len() as u32);
+    //trace chains aren't parsed so none are written, offset and count stay 0
+    Endian::write_u32(&mut data[0x64..], filename_strings_offset as u32);
+    Endian::write_u32(&mut data[0x68..], filename_strings_size as u32);
+    Endian::write_u32(&mut data[0x6c..], volumes_offset as u32);
+    Endian::write_u32(&mut data[0x70..], self.volumes.len() as u32);
+    Endian::write_u32(&mut data[0x74..], volumes_size as u32);
+
+    for (index, time) in self.file_information.last_execution_times().iter().take(layout.execution_time_count).enumerate()
+    {
+      Endian::write_u64(&mut data[layout.execution_time_offset + index * 8..], to_filetime(*time));
+    }
+    Endian::write_u32(&mut data[layout.run_count_offset..], self.file_information.number_of_execution());
+
+    Ok(data)
+  }
+}
+
+fn write_metric(data : &mut Vec<u8>, metric : &FileMetric, version : PrefetchVersion) -> anyhow::Result<()>
+{
+  data.write_u32::<Endian>(metric.start_time)?;
+  data.write_u32::<Endian>(metric.duration)?;
+  if version != PrefetchVersion::WindowsXP
+  {
+    data.write_u32::<Endian>(metric.average_duration)?;
+  }
+  data.write_u32::<Endian>(metric.filename_offset)?;
+  data.write_u32::<Endian>(metric.filename_length)?;
+  data.write_u32::<Endian>(metric.flags)?;
+  if version != PrefetchVersion::WindowsXP
+  {
+    data.write_u64::<Endian>(metric.file_reference)?;
+  }
+  Ok(())
+}
+
+fn write_utf16(data : &mut Vec<u8>, text : &str) -> anyhow::Result<()>
+{
+  for unit in text.encode_utf16()
+  {
+    data.write_u16::<Endian>(unit)?;
+  }
+  Ok(())
+}
+
+fn to_filetime(time : DateTime<Utc>) -> u64
+{
+  ((time.timestamp() + FILETIME_UNIX_DIFFERENCE) as u64) * 10_000_000 + (time.timestamp_subsec_nanos() / 100) as u64
+}
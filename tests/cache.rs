@@ -0,0 +1,23 @@
+//! Round trip of a parsed prefetch through JSON, built with the cache feature
+#![cfg(feature = "cache")]
+mod common;
+
+use tap_plugin_prefetch::Prefetch;
+
+use common::Fixture;
+
+#[test]
+fn json_round_trip()
+{
+  let fixture = Fixture::new(0x1a).executable_name("CMD.EXE").run_count(5).files(&["\\VOLUME{01}\\WINDOWS\\SYSTEM32\\CMD.EXE"])
+                                  .extra_volume("\\DEVICE\\MUP", 0xbeef, &["\\DEVICE\\MUP\\SERVER"]);
+  let prefetch = Prefetch::from_bytes(&fixture.build()).unwrap();
+
+  let cached : Prefetch = serde_json::from_str(&serde_json::to_string(&prefetch).unwrap()).unwrap();
+  assert_eq!(cached.executable_name(), "CMD.EXE");
+  assert_eq!(cached.run_count(), 5);
+  assert_eq!(cached.files, prefetch.files);
+  assert_eq!(cached.volumes[1].directories(), ["\\DEVICE\\MUP\\SERVER"]);
+  assert_eq!(cached.file_information.last_execution_times(), prefetch.file_information.last_execution_times());
+  assert_eq!(cached.summary().volume_count, prefetch.summary().volume_count);
+}
@@ -0,0 +1,40 @@
+//! Parquet export read back with the arrow reader
+#![cfg(feature = "parquet")]
+mod common;
+
+use std::fs::{self, File};
+
+use arrow::array::{Array, StringArray, TimestampMicrosecondArray, UInt32Array};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use tap_plugin_prefetch::Prefetch;
+use tap_plugin_prefetch::columnar::write_parquet;
+
+use common::{Fixture, UNIX_TIME};
+
+#[test]
+fn round_trip()
+{
+  let notepad = Prefetch::from_bytes(&Fixture::new(0x1a).executable_name("NOTEPAD.EXE").run_count(7).build()).unwrap();
+  let never_run = Prefetch::from_bytes(&Fixture::new(0x17).last_execution_time(0).build()).unwrap();
+
+  let path = std::env::temp_dir().join(format!("tap-plugin-prefetch-columnar-{}.parquet", std::process::id()));
+  write_parquet(File::create(&path).unwrap(), &[&notepad, &never_run]).unwrap();
+  let batches = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap()
+                  .collect::<Result<Vec<_>, _>>().unwrap();
+  fs::remove_file(&path).unwrap();
+
+  assert_eq!(batches.len(), 1);
+  let batch = &batches[0];
+  assert_eq!(batch.num_rows(), 2);
+
+  let names = batch.column_by_name("name").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+  assert_eq!((names.value(0), names.value(1)), ("NOTEPAD.EXE", "TEST.EXE"));
+  let run_counts = batch.column_by_name("run_count").unwrap().as_any().downcast_ref::<UInt32Array>().unwrap();
+  assert_eq!((run_counts.value(0), run_counts.value(1)), (7, 1));
+  let last_executions = batch.column_by_name("last_execution").unwrap().as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
+  assert_eq!(last_executions.value(0), UNIX_TIME * 1_000_000);
+  assert!(last_executions.is_null(1));
+  let volume_serials = batch.column_by_name("volume_serial").unwrap().as_any().downcast_ref::<UInt32Array>().unwrap();
+  assert_eq!((volume_serials.value(0), volume_serials.value(1)), (0x1234_5678, 0x1234_5678));
+}
@@ -0,0 +1,229 @@
+//! Generator of minimal valid prefetch files, so tests don't have to ship binary fixtures
+#![allow(dead_code)]
+
+/// FILETIME of 2020-09-13T12:26:40Z
+pub const FILETIME : u64 = 132_444_736_000_000_000;
+/// `FILETIME` as a unix timestamp
+pub const UNIX_TIME : i64 = 1_600_000_000;
+
+/**
+ *   Fields of the generated prefetch, `build` lay them out for `version`
+ */
+pub struct Fixture
+{
+  pub version : u32,
+  pub executable_name : String,
+  pub hash : u32,
+  pub run_count : u32,
+  pub last_execution_time : u64,
+  /// Written after the last execution time, only stored by Windows 8 and later
+  pub previous_execution_times : Vec<u64>,
+  pub files : Vec<String>,
+  /// Index of the file and NTFS file reference of each metric entry, XP entries have no file reference
+  pub metrics : Vec<(usize, u64)>,
+  pub volume_serial_number : u32,
+  pub volume_creation_date : u64,
+  /// Device path, serial number and directories of additional volumes
+  pub extra_volumes : Vec<(String, u32, Vec<String>)>,
+  /// Zero bytes written after the metrics, the file paths, the volume entries and each device path
+  pub padding : usize,
+}
+
+/// Offsets of the execution time, run count and metrics array, and size of the volume entry
+fn layout(version : u32) -> (usize, usize, usize, usize)
+{
+  match version
+  {
+    0x11 => (0x78, 0x90, 0x98, 0x28),
+    0x17 => (0x80, 0x98, 0xf0, 0x68),
+    0x1e | 0x1f => (0x80, 0xd0, 0x130, 0x60),
+    _ => (0x80, 0xd0, 0x130, 0x68),
+  }
+}
+
+fn put_u32(buffer : &mut [u8], offset : usize, value : u32)
+{
+  buffer[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buffer : &mut [u8], offset : usize, value : u64)
+{
+  buffer[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+fn utf16(text : &str) -> Vec<u8>
+{
+  text.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect()
+}
+
+impl Fixture
+{
+  pub fn new(version : u32) -> Fixture
+  {
+    Fixture{
+      version,
+      executable_name : "TEST.EXE".into(),
+      hash : 0xdead_beef,
+      run_count : 1,
+      last_execution_time : FILETIME,
+      previous_execution_times : Vec::new(),
+      files : vec!["\\VOLUME{01d0000000000000-12345678}\\TEST.EXE".into()],
+      metrics : Vec::new(),
+      volume_serial_number : 0x1234_5678,
+      volume_creation_date : FILETIME,
+      extra_volumes : Vec::new(),
+      padding : 0,
+    }
+  }
+
+  pub fn executable_name(mut self, executable_name : &str) -> Fixture
+  {
+    self.executable_name = executable_name.into();
+    self
+  }
+
+  pub fn run_count(mut self, run_count : u32) -> Fixture
+  {
+    self.run_count = run_count;
+    self
+  }
+
+  pub fn last_execution_time(mut self, last_execution_time : u64) -> Fixture
+  {
+    self.last_execution_time = last_execution_time;
+    self
+  }
+
+  pub fn previous_execution_times(mut self, previous_execution_times : &[u64]) -> Fixture
+  {
+    self.previous_execution_times = previous_execution_times.to_vec();
+    self
+  }
+
+  pub fn files(mut self, files : &[&str]) -> Fixture
+  {
+    self.files = files.iter().map(|file| file.to_string()).collect();
+    self
+  }
+
+  pub fn metric(mut self, file_index : usize, file_reference : u64) -> Fixture
+  {
+    self.metrics.push((file_index, file_reference));
+    self
+  }
+
+  pub fn extra_volume(mut self, device_path : &str, serial_number : u32, directories : &[&str]) -> Fixture
+  {
+    self.extra_volumes.push((device_path.into(), serial_number, directories.iter().map(|directory| directory.to_string()).collect()));
+    self
+  }
+
+  pub fn padding(mut self, padding : usize) -> Fixture
+  {
+    self.padding = padding;
+    self
+  }
+
+  /// File paths follow the metrics array and are followed by the volume information, separated by `padding`
+  pub fn build(&self) -> Vec<u8>
+  {
+    let (execution_time_offset, run_count_offset, metrics_offset, volume_entry_size) = layout(self.version);
+    let mut buffer = vec![0u8; metrics_offset];
+
+    put_u32(&mut buffer, 0x0, self.version);
+    buffer[0x4..0x8].copy_from_slice(b"SCCA");
+    let name = utf16(&self.executable_name);
+    buffer[0x10..0x10 + name.len().min(58)].copy_from_slice(&name[..name.len().min(58)]);
+    put_u32(&mut buffer, 0x4c, self.hash);
+
+    let mut offsets : Vec<u32> = Vec::new();
+    let mut offset = 0;
+    for file in &self.files
+    {
+      offsets.push(offset);
+      offset += (file.encode_utf16().count() as u32 + 1) * 2;
+    }
+    for (file_index, file_reference) in &self.metrics
+    {
+      let entry = buffer.len();
+      let length = self.files[*file_index].encode_utf16().count() as u32;
+      if self.version == 0x11
+      {
+        buffer.resize(entry + 20, 0);
+        put_u32(&mut buffer, entry + 8, offsets[*file_index]);
+        put_u32(&mut buffer, entry + 12, length);
+        continue
+      }
+      buffer.resize(entry + 32, 0);
+      put_u32(&mut buffer, entry + 12, offsets[*file_index]);
+      put_u32(&mut buffer, entry + 16, length);
+      put_u64(&mut buffer, entry + 24, *file_reference);
+    }
+
+    buffer.resize(buffer.len() + self.padding, 0);
+
+    let files : Vec<u8> = self.files.iter().flat_map(|file| utf16(&format!("{}\0", file))).collect();
+    put_u32(&mut buffer, 0x54, metrics_offset as u32);
+    put_u32(&mut buffer, 0x58, self.metrics.len() as u32);
+    put_u32(&mut buffer, 0x64, buffer.len() as u32);
+    put_u32(&mut buffer, 0x68, files.len() as u32);
+    buffer.extend(files);
+    buffer.resize(buffer.len() + self.padding, 0);
+
+    let volume_offset = buffer.len();
+    put_u32(&mut buffer, 0x6c, volume_offset as u32);
+    put_u32(&mut buffer, 0x70, 1 + self.extra_volumes.len() as u32);
+    buffer.resize(volume_offset + volume_entry_size * (1 + self.extra_volumes.len()) + self.padding, 0);
+    put_u64(&mut buffer, volume_offset + 0x8, self.volume_creation_date);
+    put_u32(&mut buffer, volume_offset + 0x10, self.volume_serial_number);
+    for (index, (device_path, serial_number, directories)) in self.extra_volumes.iter().enumerate()
+    {
+      let entry = volume_offset + volume_entry_size * (index + 1);
+      put_u32(&mut buffer, entry, (buffer.len() - volume_offset) as u32);
+      put_u32(&mut buffer, entry + 0x4, device_path.encode_utf16().count() as u32);
+      buffer.extend(utf16(&format!("{}\0", device_path)));
+      buffer.resize(buffer.len() + self.padding, 0);
+      put_u32(&mut buffer, entry + 0x10, *serial_number);
+      put_u32(&mut buffer, entry + 0x1c, (buffer.len() - volume_offset) as u32);
+      put_u32(&mut buffer, entry + 0x20, directories.len() as u32);
+      for directory in directories
+      {
+        buffer.extend((directory.encode_utf16().count() as u16).to_le_bytes());
+        buffer.extend(utf16(&format!("{}\0", directory)));
+      }
+    }
+    let volumes_size = (buffer.len() - volume_offset) as u32;
+    put_u32(&mut buffer, 0x74, volumes_size);
+
+    put_u64(&mut buffer, execution_time_offset, self.last_execution_time);
+    for (index, time) in self.previous_execution_times.iter().enumerate()
+    {
+      put_u64(&mut buffer, execution_time_offset + (index + 1) * 8, *time);
+    }
+    put_u32(&mut buffer, run_count_offset, self.run_count);
+
+    let size = buffer.len() as u32;
+    put_u32(&mut buffer, 0xc, size);
+    buffer
+  }
+}
+
+/// Store `data` in a MAM container using only Huffman literals
+pub fn compress(data : &[u8]) -> Vec<u8>
+{
+  let mut compressed = b"MAM\x04".to_vec();
+  compressed.extend((data.len() as u32).to_le_bytes());
+
+  for block in data.chunks(65536)
+  {
+    compressed.extend([0x88u8; 128]);
+    compressed.extend([0u8; 128]);
+    for pair in block.chunks(2)
+    {
+      compressed.push(pair.get(1).copied().unwrap_or(0));
+      compressed.push(pair[0]);
+    }
+    compressed.extend([0u8; 2]);
+  }
+  compressed
+}
@@ -0,0 +1,45 @@
+//! Raw offsets read for the `debug` plugin argument
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::debug::PrefetchDebug;
+
+use common::{compress, Fixture};
+
+#[test]
+fn offsets_of_a_valid_file()
+{
+  let buffer = Fixture::new(0x1a).extra_volume("\\DEVICE\\MUP", 0xbeef, &["\\DEVICE\\MUP\\SERVER"]).build();
+  let debug = PrefetchDebug::from_reader(&mut Cursor::new(buffer.clone()));
+
+  assert!(debug.error.is_none());
+  assert_eq!(debug.version_value, 0x1a);
+  assert_eq!(debug.volume_information_offset, u32::from_le_bytes(buffer[0x6c..0x70].try_into().unwrap()));
+  assert_eq!(debug.volumes.len(), 2);
+  assert_eq!(debug.volumes[1].offset, debug.volume_information_offset as u64 + 0x68);
+  assert_eq!(debug.volumes[1].folder_path_count, 1);
+}
+
+#[test]
+fn offsets_of_a_compressed_file()
+{
+  let debug = PrefetchDebug::from_reader(&mut Cursor::new(compress(&Fixture::new(0x1e).build())));
+
+  assert!(debug.compressed);
+  assert_eq!(debug.version_value, 0x1e);
+  assert_eq!(debug.volumes.len(), 1);
+}
+
+#[test]
+fn offsets_read_before_the_error()
+{
+  let mut buffer = Fixture::new(0x17).build();
+  buffer[0x6c..0x70].copy_from_slice(&0x7fff_0000u32.to_le_bytes());
+  let debug = PrefetchDebug::from_reader(&mut Cursor::new(buffer));
+
+  assert_eq!(debug.volume_information_offset, 0x7fff_0000);
+  assert_eq!(debug.volume_count, 1);
+  assert!(debug.volumes.is_empty());
+  assert!(debug.error.is_some());
+}
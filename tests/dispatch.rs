@@ -0,0 +1,38 @@
+//! Routing of each version dword to its file information reader
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{Prefetch, PrefetchVersion, file_information_reader};
+
+use common::Fixture;
+
+/// Run count read from a fixture of `version` storing `version` as run count
+fn run_count(version : u32) -> u32
+{
+  let fixture = Fixture::new(version).run_count(version);
+  Prefetch::from_file(&mut Cursor::new(fixture.build())).unwrap().file_information.number_of_execution()
+}
+
+#[test]
+fn known_versions_use_their_reader()
+{
+  assert_eq!(run_count(0x11), 0x11);
+  assert_eq!(run_count(0x17), 0x17);
+  assert_eq!(run_count(0x1a), 0x1a);
+}
+
+#[test]
+fn windows10_uses_windows8_layout()
+{
+  assert!(file_information_reader(PrefetchVersion::Windows10).is_some());
+  assert_eq!(run_count(0x1e), 0x1e);
+  assert_eq!(run_count(0x1f), 0x1f);
+}
+
+#[test]
+fn invalid_version_is_rejected()
+{
+  let err = Prefetch::from_file(&mut Cursor::new(Fixture::new(0x42).build())).unwrap_err();
+  assert!(err.to_string().contains("Can't match Prefetch version"));
+}
@@ -0,0 +1,37 @@
+//! Check that every reader decode the fields as little-endian
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::Prefetch;
+
+use common::{Fixture, UNIX_TIME};
+
+const RUN_COUNT : u32 = 0x0102_0304;
+
+fn check(version : u32)
+{
+  let buffer = Fixture::new(version).run_count(RUN_COUNT).build();
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+
+  assert_eq!(prefetch.file_information.number_of_execution(), RUN_COUNT);
+  assert_eq!(prefetch.file_information.last_execution_time().unwrap().timestamp(), UNIX_TIME);
+}
+
+#[test]
+fn xp_little_endian()
+{
+  check(0x11);
+}
+
+#[test]
+fn vista_little_endian()
+{
+  check(0x17);
+}
+
+#[test]
+fn windows8_little_endian()
+{
+  check(0x1a);
+}
@@ -0,0 +1,98 @@
+//! Typed errors returned for files that can't be parsed
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{Prefetch, PrefetchError, PrefetchParseOptions};
+
+use common::Fixture;
+
+#[test]
+fn bad_signature()
+{
+  let mut buffer = Fixture::new(0x17).build();
+  buffer[0x4..0x8].copy_from_slice(b"ABCD");
+
+  match Prefetch::from_file(&mut Cursor::new(buffer))
+  {
+    Err(PrefetchError::BadSignature(signature)) => assert_eq!(signature, "ABCD"),
+    result => panic!("unexpected result {:?}", result.map(|_| ())),
+  }
+}
+
+#[test]
+fn bad_signature_without_verification()
+{
+  let mut buffer = Fixture::new(0x17).build();
+  buffer[0x4..0x8].copy_from_slice(b"ABCD");
+
+  let options = PrefetchParseOptions{ verify_signature : false, ..Default::default() };
+  let prefetch = Prefetch::from_file_with_options(&mut Cursor::new(buffer), &options).unwrap();
+  assert_eq!(prefetch.header.signature(), "ABCD");
+  assert!(!prefetch.header.signature_is_valid());
+  assert_eq!(prefetch.warnings[0].field, "signature");
+}
+
+#[test]
+fn unknown_version()
+{
+  match Prefetch::from_file(&mut Cursor::new(Fixture::new(0x42).build()))
+  {
+    Err(PrefetchError::UnknownVersion(0x42)) => (),
+    result => panic!("unexpected result {:?}", result.map(|_| ())),
+  }
+}
+
+#[test]
+fn truncated()
+{
+  let mut buffer = Fixture::new(0x1a).build();
+  buffer.truncate(0x90);
+
+  match Prefetch::from_file(&mut Cursor::new(buffer))
+  {
+    Err(PrefetchError::Truncated{ offset }) => assert!(offset <= 0x90),
+    result => panic!("unexpected result {:?}", result.map(|_| ())),
+  }
+}
+
+#[test]
+fn error_is_usable_with_anyhow()
+{
+  let result : anyhow::Result<Prefetch> = Prefetch::from_file(&mut Cursor::new(Fixture::new(0x42).build())).map_err(Into::into);
+  let err = result.unwrap_err();
+  assert!(matches!(err.downcast_ref::<PrefetchError>(), Some(PrefetchError::UnknownVersion(0x42))));
+}
+
+#[test]
+fn file_path_offset_after_end_of_file()
+{
+  let mut buffer = Fixture::new(0x17).files(&["\\VOLUME{01}\\WINDOWS\\SYSTEM32\\CMD.EXE"]).build();
+  buffer[0x64..0x68].copy_from_slice(&0x7fff_0000u32.to_le_bytes());
+
+  match Prefetch::from_file(&mut Cursor::new(buffer))
+  {
+    Err(PrefetchError::InvalidOffset{ field, offset }) =>
+    {
+      assert_eq!(field, "first_file_path_offset");
+      assert_eq!(offset, 0x7fff_0000);
+    },
+    result => panic!("unexpected result {:?}", result.map(|_| ())),
+  }
+}
+
+#[test]
+fn volume_information_size_is_capped_to_the_file()
+{
+  let mut buffer = Fixture::new(0x17).extra_volume("\\VOLUME{02}", 0x1234, &[]).build();
+  //huge device path length in the second entry, would allocate gigabytes if trusted
+  let volume_offset = u32::from_le_bytes(buffer[0x6c..0x70].try_into().unwrap()) as usize;
+  buffer[0x74..0x78].copy_from_slice(&u32::MAX.to_le_bytes());
+  buffer[volume_offset + 0x68 + 0x4..volume_offset + 0x68 + 0x8].copy_from_slice(&0x4000_0000u32.to_le_bytes());
+
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+  let fields : Vec<&str> = prefetch.warnings.iter().map(|warning| warning.field.as_str()).collect();
+  assert!(fields.contains(&"volume_information_size"));
+  assert!(fields.contains(&"device_path"));
+  assert_eq!(prefetch.volumes.len(), 2);
+}
@@ -0,0 +1,130 @@
+//! Execution times stored by each version
+mod common;
+
+use std::io::Cursor;
+
+use chrono::Duration;
+
+use tap_plugin_prefetch::Prefetch;
+
+use common::{Fixture, FILETIME, UNIX_TIME};
+
+/// One hour in FILETIME units
+const HOUR : u64 = 36_000_000_000;
+/// One minute in FILETIME units
+const MINUTE : u64 = 600_000_000;
+
+fn execution_times(fixture : Fixture) -> Vec<i64>
+{
+  let prefetch = Prefetch::from_file(&mut Cursor::new(fixture.build())).unwrap();
+  prefetch.file_information.last_execution_times().iter().map(|time| time.timestamp()).collect()
+}
+
+#[test]
+fn windows8_runs_without_empty_slots()
+{
+  let fixture = Fixture::new(0x1a).previous_execution_times(&[FILETIME - HOUR, FILETIME - 2 * HOUR]);
+
+  assert_eq!(execution_times(fixture), vec![UNIX_TIME, UNIX_TIME - 3600, UNIX_TIME - 7200]);
+}
+
+#[test]
+fn windows8_all_slots()
+{
+  let previous : Vec<u64> = (1..8).map(|hours| FILETIME - hours * HOUR).collect();
+  let fixture = Fixture::new(0x1a).previous_execution_times(&previous);
+
+  assert_eq!(execution_times(fixture).len(), 8);
+}
+
+#[test]
+fn windows10_all_slots()
+{
+  let previous : Vec<u64> = (1..8).map(|hours| FILETIME - hours * HOUR).collect();
+  let expected : Vec<i64> = (0..8).map(|hours| UNIX_TIME - hours * 3600).collect();
+
+  assert_eq!(execution_times(Fixture::new(0x1e).previous_execution_times(&previous)), expected);
+  assert_eq!(execution_times(Fixture::new(0x1f).previous_execution_times(&previous)), expected);
+}
+
+#[test]
+fn single_time_before_windows8()
+{
+  assert_eq!(execution_times(Fixture::new(0x11)), vec![UNIX_TIME]);
+  assert_eq!(execution_times(Fixture::new(0x17)), vec![UNIX_TIME]);
+}
+
+#[test]
+fn summary_execution_range()
+{
+  let fixture = Fixture::new(0x1a).previous_execution_times(&[FILETIME - HOUR, FILETIME - 2 * HOUR]).files(&["A.DLL", "B.DLL"]);
+  let summary = Prefetch::from_bytes(&fixture.build()).unwrap().summary();
+
+  assert_eq!(summary.last_execution_time.unwrap().timestamp(), UNIX_TIME);
+  assert_eq!(summary.first_execution_time.unwrap().timestamp(), UNIX_TIME - 7200);
+  assert_eq!(summary.file_count, 2);
+  assert_eq!(summary.volume_count, 1);
+}
+
+#[test]
+fn empty_slots_are_omitted()
+{
+  let fixture = Fixture::new(0x1a).previous_execution_times(&[0, FILETIME - 2 * HOUR]);
+
+  assert_eq!(execution_times(fixture), vec![UNIX_TIME, UNIX_TIME - 7200]);
+}
+
+#[test]
+fn no_valid_execution_time()
+{
+  let prefetch = Prefetch::from_bytes(&Fixture::new(0x17).last_execution_time(0).build()).unwrap();
+
+  assert!(prefetch.file_information.last_execution_time().is_none());
+  assert!(prefetch.file_information.last_execution_times().is_empty());
+  assert!(prefetch.warnings.is_empty());
+}
+
+#[test]
+fn invalid_filetimes_are_warnings()
+{
+  let mut buffer = Fixture::new(0x1a).previous_execution_times(&[u64::MAX]).build();
+  let volume_offset = u32::from_le_bytes(buffer[0x6c..0x70].try_into().unwrap()) as usize;
+  buffer[volume_offset + 0x8..volume_offset + 0x10].copy_from_slice(&(1u64 << 63).to_le_bytes());
+  let prefetch = Prefetch::from_bytes(&buffer).unwrap();
+
+  assert_eq!(prefetch.file_information.last_execution_times().len(), 1);
+  assert!(prefetch.volume().unwrap().volume_creation_date().is_none());
+  let warnings : Vec<(&str, Option<u64>)> = prefetch.warnings.iter().map(|warning| (warning.field.as_str(), warning.offset)).collect();
+  assert_eq!(warnings, vec![("last_execution_times", Some(0x88)), ("volume_creation_date", Some(volume_offset as u64 + 0x8))]);
+}
+
+fn burst_sizes(fixture : Fixture, window : Duration) -> Vec<usize>
+{
+  let prefetch = Prefetch::from_bytes(&fixture.build()).unwrap();
+  prefetch.execution_bursts(window).iter().map(|burst| burst.len()).collect()
+}
+
+#[test]
+fn burst_window_boundary_is_included()
+{
+  let fixture = || Fixture::new(0x1a).previous_execution_times(&[FILETIME - 5 * MINUTE, FILETIME - 10 * MINUTE]);
+
+  assert_eq!(burst_sizes(fixture(), Duration::minutes(5)), vec![3]);
+  assert_eq!(burst_sizes(fixture(), Duration::minutes(5) - Duration::seconds(1)), vec![1, 1, 1]);
+}
+
+#[test]
+fn several_bursts()
+{
+  //two runs a day ago, three runs an hour ago and the last one
+  let previous = [FILETIME - HOUR, FILETIME - HOUR - MINUTE, FILETIME - HOUR - 2 * MINUTE,
+                  FILETIME - 24 * HOUR, FILETIME - 24 * HOUR - MINUTE];
+  let prefetch = Prefetch::from_bytes(&Fixture::new(0x1e).previous_execution_times(&previous).build()).unwrap();
+  let bursts = prefetch.execution_bursts(Duration::minutes(2));
+
+  let starts : Vec<i64> = bursts.iter().map(|burst| burst[0].timestamp()).collect();
+  assert_eq!(starts, vec![UNIX_TIME - 24 * 3600 - 60, UNIX_TIME - 3600 - 120, UNIX_TIME]);
+  let sizes : Vec<usize> = bursts.iter().map(|burst| burst.len()).collect();
+  assert_eq!(sizes, vec![2, 3, 1]);
+  assert!(bursts.iter().all(|burst| burst.windows(2).all(|pair| pair[0] <= pair[1])));
+}
@@ -0,0 +1,77 @@
+//! Decomposition of the NTFS file reference of file metrics and stride of the metrics array
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{FileMetric, Prefetch, PrefetchVersion};
+
+use common::Fixture;
+
+const FILES : [&str; 2] = ["\\VOLUME{01}\\A.DLL", "\\VOLUME{01}\\LONGER.DLL"];
+
+#[test]
+fn mft_entry_and_sequence()
+{
+  let metric = FileMetric{ file_reference : 0x0003_0000_0001_e240, ..Default::default() };
+
+  assert_eq!(metric.mft_entry(), 123456);
+  assert_eq!(metric.mft_sequence(), 3);
+}
+
+/// Second metric of a fixture with two entries, a wrong stride would misread its fields
+fn second_metric(version : u32) -> FileMetric
+{
+  let fixture = Fixture::new(version).files(&FILES).metric(0, 0x0001_0000_0000_0010).metric(1, 0x0002_0000_0000_0020);
+  let prefetch = Prefetch::from_file(&mut Cursor::new(fixture.build())).unwrap();
+
+  assert_eq!(prefetch.metrics().len(), 2);
+  prefetch.metrics()[1].clone()
+}
+
+#[test]
+fn xp_stride()
+{
+  assert_eq!(FileMetric::entry_size(PrefetchVersion::WindowsXP), 20);
+  let metric = second_metric(0x11);
+
+  //"\VOLUME{01}\A.DLL" and its NUL
+  assert_eq!((metric.filename_offset, metric.filename_length), (36, 22));
+  assert_eq!(metric.file_reference, 0);
+}
+
+fn check_32_bytes_stride(version : u32)
+{
+  let metric = second_metric(version);
+
+  assert_eq!((metric.filename_offset, metric.filename_length), (36, 22));
+  assert_eq!((metric.mft_entry(), metric.mft_sequence()), (0x20, 2));
+}
+
+#[test]
+fn vista_stride()
+{
+  assert_eq!(FileMetric::entry_size(PrefetchVersion::WindowsVista), 32);
+  check_32_bytes_stride(0x17);
+}
+
+#[test]
+fn windows8_stride()
+{
+  assert_eq!(FileMetric::entry_size(PrefetchVersion::Windows8), 32);
+  check_32_bytes_stride(0x1a);
+}
+
+#[test]
+fn windows10_stride()
+{
+  assert_eq!(FileMetric::entry_size(PrefetchVersion::Windows10), 32);
+  check_32_bytes_stride(0x1e);
+}
+
+#[test]
+fn flags_are_kept_raw()
+{
+  let prefetch = Prefetch::from_bytes(include_bytes!("data/windows10_compressed.pf")).unwrap();
+
+  assert!(prefetch.metrics().iter().all(|metric| metric.flags == 0x2));
+}
@@ -0,0 +1,71 @@
+//! Lazy decoding of the file path list
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{Prefetch, PrefetchParseOptions};
+
+use common::Fixture;
+
+const FILES : &[&str] = &["\\VOLUME{01}\\WINDOWS\\SYSTEM32\\NTDLL.DLL", "\\VOLUME{01}\\TEMP\\A.DLL", "\\VOLUME{01}\\TEMP\\B.DLL"];
+
+#[test]
+fn iterate_without_the_file_list()
+{
+  let mut file = Cursor::new(Fixture::new(0x1a).files(FILES).build());
+  let options = PrefetchParseOptions{ skip_file_list : true, ..Default::default() };
+  let prefetch = Prefetch::from_file_with_options(&mut file, &options).unwrap();
+  assert!(prefetch.files.is_empty());
+
+  let paths : Vec<String> = prefetch.files_iter(&mut file).collect::<anyhow::Result<_>>().unwrap();
+  assert_eq!(paths, FILES);
+}
+
+#[test]
+fn stop_early()
+{
+  let mut file = Cursor::new(Fixture::new(0x17).files(FILES).build());
+  let prefetch = Prefetch::from_file(&mut file).unwrap();
+
+  let first_temp = prefetch.files_iter(&mut file).map(Result::unwrap).find(|path| path.contains("\\TEMP\\"));
+  assert_eq!(first_temp.as_deref(), Some(FILES[1]));
+}
+
+#[test]
+fn path_cut_by_the_end_of_the_list()
+{
+  let mut buffer = Fixture::new(0x1a).files(FILES).build();
+  let size = u32::from_le_bytes(buffer[0x68..0x6c].try_into().unwrap());
+  buffer[0x68..0x6c].copy_from_slice(&(size - 4).to_le_bytes());
+  let mut file = Cursor::new(buffer);
+  let prefetch = Prefetch::from_file(&mut file).unwrap();
+
+  assert_eq!(prefetch.files_iter(&mut file).count(), 2);
+}
+
+#[test]
+fn list_cut_by_the_end_of_file()
+{
+  let mut buffer = Fixture::new(0x1a).files(FILES).build();
+  let offset = u32::from_le_bytes(buffer[0x64..0x68].try_into().unwrap()) as usize;
+  let third = offset + (FILES[0].len() + 1 + FILES[1].len() + 1) * 2;
+  buffer.truncate(third + 6);
+
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+  assert_eq!(prefetch.files, &FILES[..2]);
+  assert!(prefetch.volumes.is_empty());
+  let fields : Vec<&str> = prefetch.warnings.iter().map(|warning| warning.field.as_str()).collect();
+  assert!(fields.contains(&"files"));
+  assert!(fields.contains(&"volume_information_offset"));
+}
+
+#[test]
+fn list_cut_by_the_end_of_file_on_a_string_end()
+{
+  let mut buffer = Fixture::new(0x1a).files(FILES).build();
+  let offset = u32::from_le_bytes(buffer[0x64..0x68].try_into().unwrap()) as usize;
+  buffer.truncate(offset + (FILES[0].len() + 1) * 2);
+
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+  assert_eq!(prefetch.files, &FILES[..1]);
+}
@@ -0,0 +1,62 @@
+//! Alignment of the file references with the file paths
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{FileReference, Prefetch};
+
+use common::Fixture;
+
+#[test]
+fn references_follow_file_order()
+{
+  let fixture = Fixture::new(0x1a).files(&["\\VOLUME{01}\\A.DLL", "\\VOLUME{01}\\LONGER.DLL", "\\VOLUME{01}\\C.DLL"])
+                                   .metric(2, 0x0002_0000_0000_0030)
+                                   .metric(0, 0x0001_0000_0000_0010);
+  let prefetch = Prefetch::from_file(&mut Cursor::new(fixture.build())).unwrap();
+
+  assert_eq!(prefetch.file_references, vec![
+    FileReference{ mft_entry : 0x10, sequence_number : 1 },
+    FileReference::default(),
+    FileReference{ mft_entry : 0x30, sequence_number : 2 },
+  ]);
+}
+
+/// Name offsets written as libscca documents them, in bytes from the start of the filename strings
+#[test]
+fn filename_offsets_are_in_bytes()
+{
+  let fixture = Fixture::new(0x1a).files(&["\\VOLUME{01}\\A.DLL", "\\VOLUME{01}\\LONGER.DLL", "\\VOLUME{01}\\C.DLL"])
+                                   .metric(2, 0x0002_0000_0000_0030)
+                                   .metric(1, 0x0001_0000_0000_0010);
+  let mut data = fixture.build();
+  //"\VOLUME{01}\A.DLL" is 17 characters and "\VOLUME{01}\LONGER.DLL" 22, plus their NUL
+  data[0x130 + 12..0x130 + 16].copy_from_slice(&82u32.to_le_bytes());
+  data[0x150 + 12..0x150 + 16].copy_from_slice(&36u32.to_le_bytes());
+  let prefetch = Prefetch::from_file(&mut Cursor::new(data)).unwrap();
+
+  assert_eq!(prefetch.file_references, vec![
+    FileReference::default(),
+    FileReference{ mft_entry : 0x10, sequence_number : 1 },
+    FileReference{ mft_entry : 0x30, sequence_number : 2 },
+  ]);
+}
+
+#[test]
+fn compressed_sample_references()
+{
+  let prefetch = Prefetch::from_bytes(include_bytes!("data/windows10_compressed.pf")).unwrap();
+
+  assert_eq!(prefetch.file_references, vec![
+    FileReference{ mft_entry : 0x1000, sequence_number : 3 },
+    FileReference{ mft_entry : 0x1001, sequence_number : 3 },
+  ]);
+}
+
+#[test]
+fn one_reference_per_file()
+{
+  let prefetch = Prefetch::from_file(&mut Cursor::new(Fixture::new(0x17).build())).unwrap();
+
+  assert_eq!(prefetch.file_references.len(), prefetch.files.len());
+}
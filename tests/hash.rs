@@ -0,0 +1,101 @@
+//! Check of the header hash against the executable path and the file name, with the hashes of the
+//! prefetch files of NOTEPAD.EXE, CMD.EXE, CALC.EXE and EXPLORER.EXE from default XP and Windows 7 installations
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{Prefetch, PrefetchParseOptions};
+use tap_plugin_prefetch::hash::HashAlgorithm;
+
+use common::Fixture;
+
+/// Windows 7 use a second volume, the first one is the System Reserved partition
+const NOTEPAD_WINDOWS7 : &str = "\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\NOTEPAD.EXE";
+const NOTEPAD_XP : &str = "\\DEVICE\\HARDDISKVOLUME1\\WINDOWS\\SYSTEM32\\NOTEPAD.EXE";
+
+fn parse(fixture : Fixture) -> Prefetch
+{
+  Prefetch::from_file(&mut Cursor::new(fixture.build())).unwrap()
+}
+
+#[test]
+fn xp_hashes()
+{
+  assert_eq!(HashAlgorithm::Xp.hash(NOTEPAD_XP), 0x336351a9);
+  assert_eq!(HashAlgorithm::Xp.hash("\\DEVICE\\HARDDISKVOLUME1\\WINDOWS\\SYSTEM32\\CMD.EXE"), 0x087b4001);
+  assert_eq!(HashAlgorithm::Xp.hash("\\DEVICE\\HARDDISKVOLUME1\\WINDOWS\\EXPLORER.EXE"), 0x082f38a9);
+}
+
+#[test]
+fn windows7_hashes()
+{
+  for algorithm in [HashAlgorithm::Vista, HashAlgorithm::Windows2008]
+  {
+    assert_eq!(algorithm.hash(NOTEPAD_WINDOWS7), 0xd8414f97);
+    assert_eq!(algorithm.hash("\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\CMD.EXE"), 0x4a81b364);
+    assert_eq!(algorithm.hash("\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\CALC.EXE"), 0x77fdf17f);
+  }
+}
+
+#[test]
+fn paths_are_hashed_in_upper_case()
+{
+  assert_eq!(HashAlgorithm::Windows2008.hash(&NOTEPAD_WINDOWS7.to_lowercase()), 0xd8414f97);
+}
+
+#[test]
+fn device_path_hash()
+{
+  let mut fixture = Fixture::new(0x17).executable_name("NOTEPAD.EXE").files(&[NOTEPAD_WINDOWS7]);
+  fixture.hash = 0xd8414f97;
+  assert_eq!(parse(fixture).hash_is_valid, Some(true));
+
+  let mut fixture = Fixture::new(0x11).executable_name("NOTEPAD.EXE").files(&[NOTEPAD_XP]);
+  fixture.hash = 0x336351a9;
+  assert_eq!(parse(fixture).hash_is_valid, Some(true));
+
+  let mut fixture = Fixture::new(0x17).executable_name("NOTEPAD.EXE").files(&[NOTEPAD_WINDOWS7]);
+  fixture.hash = 0x336351a9;
+  assert_eq!(parse(fixture).hash_is_valid, Some(false));
+}
+
+#[test]
+fn volume_path_fallback_to_file_name()
+{
+  let mut fixture = Fixture::new(0x1a).executable_name("NOTEPAD.EXE");
+  fixture.hash = 0xd8414f97;
+  let mut prefetch = parse(fixture);
+  assert_eq!(prefetch.hash_is_valid, None);
+
+  prefetch.check_file_name_hash("NOTEPAD.EXE-336351A9.pf");
+  assert_eq!(prefetch.hash_is_valid, Some(false));
+
+  prefetch.hash_is_valid = None;
+  prefetch.check_file_name_hash("NOTEPAD.EXE-D8414F97.pf");
+  assert_eq!(prefetch.hash_is_valid, Some(true));
+}
+
+#[test]
+fn unparsable_file_name()
+{
+  let mut prefetch = parse(Fixture::new(0x1a));
+  prefetch.check_file_name_hash("test.bin");
+
+  assert_eq!(prefetch.hash_is_valid, None);
+}
+
+#[test]
+fn node_name_fallback()
+{
+  //the plugin parse with the name of the file node
+  let mut fixture = Fixture::new(0x1e).executable_name("NOTEPAD.EXE");
+  fixture.hash = 0xd8414f97;
+  let options = PrefetchParseOptions::default();
+
+  let prefetch = Prefetch::from_named_file(&mut Cursor::new(fixture.build()), "NOTEPAD.EXE-D8414F97.pf", &options).unwrap();
+  assert_eq!(prefetch.hash_is_valid, Some(true));
+  let prefetch = Prefetch::from_named_file(&mut Cursor::new(fixture.build()), "NOTEPAD.EXE-336351A9.pf", &options).unwrap();
+  assert_eq!(prefetch.hash_is_valid, Some(false));
+  let prefetch = Prefetch::from_named_file(&mut Cursor::new(fixture.build()), "prefetch", &options).unwrap();
+  assert_eq!(prefetch.hash_is_valid, None);
+}
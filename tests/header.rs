@@ -0,0 +1,105 @@
+//! Parsing of the header alone
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{looks_like_prefetch, Prefetch, PrefetchVersion, MAX_REASONABLE_PREFETCH_SIZE};
+
+use common::{compress, Fixture};
+
+#[test]
+fn header_fields()
+{
+  let buffer = Fixture::new(0x17).executable_name("CMD.EXE").build();
+  let header = Prefetch::read_header(&mut Cursor::new(buffer)).unwrap();
+
+  assert_eq!(header.version(), PrefetchVersion::WindowsVista);
+  assert_eq!(header.signature(), "SCCA");
+  assert_eq!(header.file_name(), "CMD.EXE");
+}
+
+#[test]
+fn header_of_truncated_file()
+{
+  let mut buffer = Fixture::new(0x1a).build();
+  buffer.truncate(0x90);
+
+  assert!(Prefetch::from_file(&mut Cursor::new(buffer.clone())).is_err());
+  assert_eq!(Prefetch::read_header(&mut Cursor::new(buffer)).unwrap().version(), PrefetchVersion::Windows8);
+}
+
+#[test]
+fn compressed_header()
+{
+  let buffer = compress(&Fixture::new(0x1e).executable_name("CMD.EXE").build());
+  let header = Prefetch::read_header(&mut Cursor::new(buffer)).unwrap();
+
+  assert_eq!(header.version(), PrefetchVersion::Windows10);
+  assert_eq!(header.file_name(), "CMD.EXE");
+}
+
+#[test]
+fn prefetch_accessors()
+{
+  let buffer = Fixture::new(0x17).executable_name("CMD.EXE").run_count(7).build();
+  let size = buffer.len() as u32;
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+
+  assert_eq!(prefetch.executable_name(), "CMD.EXE");
+  assert_eq!(prefetch.run_count(), 7);
+  assert_eq!(prefetch.volume_serial(), 0x1234_5678);
+  assert_eq!(prefetch.file_size(), size);
+}
+
+#[test]
+fn padding_is_trimmed()
+{
+  let buffer = Fixture::new(0x1a).executable_name("NOTEPAD.EXE").extra_volume("\\VOLUME{02}", 0x1234, &[]).build();
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+
+  assert_eq!(prefetch.executable_name(), "NOTEPAD.EXE");
+  assert!(prefetch.volumes.iter().all(|volume| !volume.device_path().contains('\0')));
+}
+
+#[test]
+fn detection()
+{
+  let buffer = Fixture::new(0x1a).build();
+  assert!(looks_like_prefetch(&buffer));
+  assert!(looks_like_prefetch(&compress(&buffer)));
+
+  let mut bad_signature = buffer.clone();
+  bad_signature[0x4..0x8].copy_from_slice(b"ABCD");
+  assert!(!looks_like_prefetch(&bad_signature));
+  assert!(!looks_like_prefetch(&buffer[..0x8]));
+}
+
+#[test]
+fn compressed_detection_checks_the_size()
+{
+  let mut compressed = compress(&Fixture::new(0x1e).build());
+  compressed[0x4..0x8].copy_from_slice(&0x10u32.to_le_bytes());
+  assert!(!looks_like_prefetch(&compressed));
+
+  compressed[0x4..0x8].copy_from_slice(&(MAX_REASONABLE_PREFETCH_SIZE as u32 + 1).to_le_bytes());
+  assert!(!looks_like_prefetch(&compressed));
+
+  assert!(!looks_like_prefetch(b"MAM\x04"));
+}
+
+#[test]
+fn trailing_slack_after_the_trace_chains()
+{
+  for (version, entry_size) in [(0x11, 12), (0x17, 12), (0x1a, 12), (0x1e, 8)]
+  {
+    let mut buffer = Fixture::new(version).build();
+    let end = buffer.len() as u32;
+    assert_eq!(Prefetch::from_bytes(&buffer).unwrap().trailing_slack_size(), 0);
+
+    //trace chains declared after the volume information, then 0x10 bytes of slack
+    buffer[0x5c..0x60].copy_from_slice(&end.to_le_bytes());
+    buffer[0x60..0x64].copy_from_slice(&4u32.to_le_bytes());
+    buffer[0xc..0x10].copy_from_slice(&(end + 4 * entry_size + 0x10).to_le_bytes());
+    assert_eq!(Prefetch::from_bytes(&buffer).unwrap().trailing_slack_size(), 0x10);
+  }
+}
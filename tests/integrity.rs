@@ -0,0 +1,85 @@
+//! Confidence level of the integrity report
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{Prefetch, PrefetchParseOptions};
+use tap_plugin_prefetch::integrity::Confidence;
+
+use common::Fixture;
+
+fn parse(buffer : Vec<u8>) -> Prefetch
+{
+  Prefetch::from_file(&mut Cursor::new(buffer)).unwrap()
+}
+
+#[test]
+fn high_without_failure()
+{
+  let report = parse(Fixture::new(0x1a).build()).integrity_report();
+
+  assert!(report.signature_valid && report.file_size_consistent && report.offsets_in_bounds && report.run_count_plausible);
+  assert_eq!(report.hash_verified, None);
+  assert_eq!(report.warning_count, 0);
+  assert_eq!(report.confidence, Confidence::High);
+}
+
+#[test]
+fn medium_with_one_failure()
+{
+  let mut buffer = Fixture::new(0x1a).build();
+  buffer[0xc..0x10].copy_from_slice(&0x100u32.to_le_bytes());
+  let report = parse(buffer).integrity_report();
+
+  assert!(!report.file_size_consistent);
+  assert_eq!(report.warning_count, 0);
+  assert_eq!(report.confidence, Confidence::Medium);
+}
+
+#[test]
+fn low_with_two_failures()
+{
+  //an implausible run count is also reported as a parse warning
+  let report = parse(Fixture::new(0x1a).run_count(200_000).build()).integrity_report();
+
+  assert!(report.file_size_consistent);
+  assert!(!report.run_count_plausible);
+  assert_eq!(report.warning_count, 1);
+  assert_eq!(report.confidence, Confidence::Low);
+}
+
+#[test]
+fn low_with_a_bad_signature()
+{
+  let mut buffer = Fixture::new(0x1a).build();
+  buffer[0x4..0x8].copy_from_slice(b"ABCD");
+  let options = PrefetchParseOptions{ verify_signature : false, ..Default::default() };
+  let report = Prefetch::from_file_with_options(&mut Cursor::new(buffer), &options).unwrap().integrity_report();
+
+  assert!(!report.signature_valid);
+  assert!(report.file_size_consistent && report.offsets_in_bounds && report.run_count_plausible);
+  assert_eq!(report.confidence, Confidence::Low);
+}
+
+#[test]
+fn windows8_trace_chains_within_the_file_size()
+{
+  //four 12 bytes trace chain entries after the volume information
+  let build = |file_size_after : u32|
+  {
+    let mut buffer = Fixture::new(0x1a).build();
+    let end = buffer.len() as u32;
+    buffer[0x5c..0x60].copy_from_slice(&end.to_le_bytes());
+    buffer[0x60..0x64].copy_from_slice(&4u32.to_le_bytes());
+    buffer[0xc..0x10].copy_from_slice(&(end + file_size_after).to_le_bytes());
+    buffer
+  };
+
+  let report = parse(build(48)).integrity_report();
+  assert!(report.file_size_consistent);
+  assert_eq!(report.confidence, Confidence::High);
+
+  let report = parse(build(40)).integrity_report();
+  assert!(!report.file_size_consistent);
+  assert_eq!(report.confidence, Confidence::Medium);
+}
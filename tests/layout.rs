@@ -0,0 +1,66 @@
+//! Offsets of the Windows 10 fields documented on `FileInformation::w10_from_reader`, read from the raw bytes
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{FileInformationHeader, Prefetch};
+
+use common::{Fixture, FILETIME};
+
+/// One hour in FILETIME units
+const HOUR : u64 = 36_000_000_000;
+
+fn u32_at(data : &[u8], offset : usize) -> u32
+{
+  u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(data : &[u8], offset : usize) -> u64
+{
+  u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn check_windows10_offsets(version : u32)
+{
+  let previous : Vec<u64> = (1..8).map(|hours| FILETIME - hours * HOUR).collect();
+  let fixture = Fixture::new(version).run_count(77)
+                                     .previous_execution_times(&previous)
+                                     .files(&["\\VOLUME{01}\\A.EXE", "\\VOLUME{01}\\B.DLL"])
+                                     .metric(0, 0x0001_0000_0000_0010)
+                                     .metric(1, 0x0001_0000_0000_0020)
+                                     .extra_volume("\\VOLUME{02}", 0xcafe, &["\\VOLUME{02}\\TOOLS"]);
+  let data = fixture.build();
+  let prefetch = Prefetch::from_bytes(&data).unwrap();
+  let header = FileInformationHeader::from_reader(&mut Cursor::new(&data)).unwrap();
+
+  assert_eq!(header.metrics_offset, u32_at(&data, 0x54));
+  assert_eq!(u32_at(&data, 0x58) as usize, prefetch.metrics().len());
+  assert_eq!((header.trace_chains_offset, header.trace_chains_count), (u32_at(&data, 0x5c), u32_at(&data, 0x60)));
+  assert_eq!((header.filename_strings_offset, header.filename_strings_size), (u32_at(&data, 0x64), u32_at(&data, 0x68)));
+  assert_eq!(header.volumes_information_offset, u32_at(&data, 0x6c));
+  assert_eq!(u32_at(&data, 0x70) as usize, prefetch.volumes.len());
+  assert_eq!(header.volumes_information_size, u32_at(&data, 0x74));
+
+  //the second volume entry start 0x60 bytes after the first one
+  let volumes = u32_at(&data, 0x6c) as usize;
+  assert_eq!(u32_at(&data, volumes + 0x60 + 0x10), prefetch.volumes[1].volume_serial_number());
+
+  let times : Vec<u64> = (0..8).map(|index| u64_at(&data, 0x80 + index * 8)).collect();
+  assert_eq!(times[0], FILETIME);
+  assert_eq!(times[1..], previous[..]);
+  assert_eq!(prefetch.file_information.last_execution_times().len(), 8);
+  assert_eq!(u32_at(&data, 0xd0), prefetch.run_count());
+  assert_eq!(prefetch.run_count(), 77);
+}
+
+#[test]
+fn windows10_offsets()
+{
+  check_windows10_offsets(0x1e);
+}
+
+#[test]
+fn windows11_offsets()
+{
+  check_windows10_offsets(0x1f);
+}
@@ -0,0 +1,42 @@
+//! Executable and file paths mapped to drive letters
+mod common;
+
+use std::collections::HashMap;
+
+use tap_plugin_prefetch::Prefetch;
+
+use common::Fixture;
+
+const FILES : &[&str] = &["\\VOLUME{01d0000000000000-12345678}\\WINDOWS\\SYSTEM32\\NTDLL.DLL",
+                          "\\DEVICE\\HARDDISKVOLUME2\\TOOLS\\TEST.EXE", "\\DEVICE\\MUP\\SERVER\\SHARE\\A.DLL"];
+
+fn parse() -> Prefetch
+{
+  Prefetch::from_bytes(&Fixture::new(0x1a).files(FILES).build()).unwrap()
+}
+
+#[test]
+fn lookup_path_with_a_mapping()
+{
+  let mapping = HashMap::from([("\\Device\\HarddiskVolume2".to_string(), 'D')]);
+
+  assert_eq!(parse().executable_lookup_path(Some(&mapping)).as_deref(), Some("D:\\TOOLS\\TEST.EXE"));
+}
+
+#[test]
+fn lookup_path_relative_to_an_unmapped_volume()
+{
+  let mapping = HashMap::from([("\\VOLUME{01d0000000000000-12345678}".to_string(), 'C')]);
+
+  assert_eq!(parse().executable_lookup_path(None).as_deref(), Some("\\TOOLS\\TEST.EXE"));
+  assert_eq!(parse().executable_lookup_path(Some(&mapping)).as_deref(), Some("\\TOOLS\\TEST.EXE"));
+}
+
+#[test]
+fn resolved_files()
+{
+  let mapping = HashMap::from([("\\VOLUME{01d0000000000000-12345678}".to_string(), 'C'), ("\\DEVICE\\HARDDISKVOLUME2".to_string(), 'D')]);
+
+  assert_eq!(parse().resolved_files(Some(&mapping)), vec!["C:\\WINDOWS\\SYSTEM32\\NTDLL.DLL", "D:\\TOOLS\\TEST.EXE", FILES[2]]);
+  assert_eq!(parse().resolved_files(None), FILES);
+}
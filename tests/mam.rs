@@ -0,0 +1,164 @@
+//! Decompression of MAM streams and parsing of compressed prefetch
+mod common;
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use tap_plugin_prefetch::{Prefetch, PrefetchError, PrefetchVersion, MAX_REASONABLE_PREFETCH_SIZE};
+use tap_plugin_prefetch::mam::{decompress, MamReader};
+
+use common::{compress, Fixture, UNIX_TIME};
+
+/// Synthetic Windows 10 prefetch of NOTEPAD.EXE, with a placeholder volume GUID and serial, compressed with
+/// Huffman coded literals and matches including matches with one and two byte extended lengths.
+/// The decompressed file is 67944 bytes long so its volume information, at 0x10800, is in the second block.
+const WINDOWS10_COMPRESSED : &[u8] = include_bytes!("data/windows10_compressed.pf");
+
+fn data() -> Vec<u8>
+{
+  (0..100_000u32).map(|index| (index % 251) as u8).collect()
+}
+
+#[test]
+fn decompress_complete_stream()
+{
+  let data = data();
+
+  assert_eq!(decompress(Cursor::new(compress(&data))).unwrap(), data);
+}
+
+#[test]
+fn decompress_truncated_stream()
+{
+  let mut compressed = compress(&data());
+  compressed.truncate(compressed.len() / 2);
+
+  let err = decompress(Cursor::new(compressed)).unwrap_err();
+  match err.downcast_ref::<PrefetchError>()
+  {
+    Some(PrefetchError::DecompressedSizeMismatch{ expected, actual }) =>
+    {
+      assert_eq!(*expected, 100_000);
+      assert!(*actual < *expected);
+    },
+    _ => panic!("unexpected error {}", err),
+  }
+}
+
+#[test]
+fn uncompressed_size_is_capped()
+{
+  let mut compressed = compress(&data());
+  compressed[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+
+  let err = decompress(Cursor::new(compressed.clone())).unwrap_err();
+  match err.downcast_ref::<PrefetchError>()
+  {
+    Some(PrefetchError::TooLarge{ size }) => assert!(*size > MAX_REASONABLE_PREFETCH_SIZE),
+    _ => panic!("unexpected error {}", err),
+  }
+  assert!(matches!(Prefetch::from_bytes(&compressed), Err(PrefetchError::TooLarge{ .. })));
+}
+
+#[test]
+fn parse_compressed_prefetch()
+{
+  let buffer = compress(&Fixture::new(0x1e).run_count(42).build());
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+
+  assert!(prefetch.compressed);
+  assert_eq!(prefetch.file_information.number_of_execution(), 42);
+  assert_eq!(prefetch.files.len(), 1);
+}
+
+#[test]
+fn parse_from_bytes()
+{
+  let data = Fixture::new(0x1a).run_count(3).build();
+  assert_eq!(Prefetch::from_bytes(&data).unwrap().run_count(), 3);
+  assert_eq!(Prefetch::from_bytes(&compress(&data)).unwrap().run_count(), 3);
+}
+
+#[test]
+fn decompress_huffman_sample()
+{
+  let data = decompress(Cursor::new(WINDOWS10_COMPRESSED)).unwrap();
+
+  assert_eq!(data.len(), 67944);
+  assert_eq!(&data[0..8], b"\x1e\x00\x00\x00SCCA");
+  assert!(data[0x300..0x10800].iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn parse_huffman_sample()
+{
+  let prefetch = Prefetch::from_bytes(WINDOWS10_COMPRESSED).unwrap();
+  let volume_path = "\\VOLUME{01d5c8a1b2c3d4e5-12345678}";
+
+  assert!(prefetch.compressed);
+  assert_eq!(prefetch.header.version(), PrefetchVersion::Windows10);
+  assert_eq!(prefetch.executable_name(), "NOTEPAD.EXE");
+  assert_eq!(prefetch.run_count(), 42);
+
+  let times : Vec<i64> = prefetch.file_information.last_execution_times().iter().map(|time| time.timestamp()).collect();
+  assert_eq!(times, (0..8).map(|index| UNIX_TIME - index * 3600).collect::<Vec<i64>>());
+
+  assert_eq!(prefetch.files, [format!("{}\\WINDOWS\\SYSTEM32\\NOTEPAD.EXE", volume_path),
+                              format!("{}\\WINDOWS\\SYSTEM32\\NTDLL.DLL", volume_path)]);
+  assert_eq!(prefetch.volumes.len(), 1);
+  assert_eq!(prefetch.volumes[0].device_path(), volume_path);
+  assert_eq!(prefetch.volumes[0].volume_serial_number(), 0x1234_abcd);
+  assert_eq!(prefetch.volumes[0].directories(), [format!("{}\\WINDOWS", volume_path),
+                                                 format!("{}\\WINDOWS\\SYSTEM32", volume_path)]);
+  assert!(prefetch.warnings.is_empty());
+}
+
+/// Literal `A` followed by a single match of 1000000 bytes at offset 1, both symbols have a one bit code
+fn long_match() -> Vec<u8>
+{
+  let mut compressed = b"MAM\x04".to_vec();
+  compressed.extend(1_000_001u32.to_le_bytes());
+  let mut lengths = [0u8; 256];
+  lengths[b'A' as usize / 2] = 0x10;
+  //match symbol 256 + 15, extended length without offset bits
+  lengths[271 / 2] = 0x10;
+  compressed.extend(lengths);
+  compressed.extend([0x00, 0x40, 0x00, 0x00]);
+  compressed.push(0xff);
+  compressed.extend(0u16.to_le_bytes());
+  compressed.extend((1_000_000u32 - 3).to_le_bytes());
+  compressed
+}
+
+#[test]
+fn long_match_window_is_bounded()
+{
+  let mut reader = MamReader::with_window_size(Cursor::new(long_match()), 0).unwrap();
+  let mut buffer = [0u8; 4096];
+  let mut size = 0;
+  loop
+  {
+    let count = reader.read(&mut buffer).unwrap();
+    if count == 0
+    {
+      break
+    }
+    assert!(buffer[..count].iter().all(|byte| *byte == b'A'));
+    assert!(reader.window_len() <= 2 * 65536);
+    size += count;
+  }
+  assert_eq!(size, 1_000_001);
+  assert!(!reader.truncated());
+
+  reader.seek(SeekFrom::Start(10)).unwrap();
+  assert_eq!(reader.read(&mut buffer[..1]).unwrap(), 1);
+  assert_eq!(buffer[0], b'A');
+}
+
+#[test]
+fn truncated_compressed_prefetch()
+{
+  let mut compressed = compress(&Fixture::new(0x1e).build());
+  compressed.truncate(compressed.len() - 64);
+
+  assert!(Prefetch::from_bytes(&compressed).is_err());
+}
@@ -0,0 +1,44 @@
+//! Correlation of the file references with an MFT
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::Prefetch;
+use tap_plugin_prefetch::mft::MftResolver;
+
+use common::Fixture;
+
+/// MFT where entry 0x10 is A.DLL and entry 0x30 was reused by RENAMED.DLL
+struct Mft;
+
+impl MftResolver for Mft
+{
+  fn resolve(&self, entry : u64, sequence : u16) -> Option<String>
+  {
+    match (entry, sequence)
+    {
+      (0x10, 1) => Some("A.DLL".into()),
+      (0x30, 2) => Some("RENAMED.DLL".into()),
+      _ => None,
+    }
+  }
+}
+
+#[test]
+fn metrics_in_a_different_order_than_the_paths()
+{
+  let fixture = Fixture::new(0x1a).files(&["\\VOLUME{01}\\A.DLL", "\\VOLUME{01}\\B.DLL", "\\VOLUME{01}\\C.DLL"])
+                                   .metric(2, 0x0002_0000_0000_0030)
+                                   .metric(0, 0x0001_0000_0000_0010);
+  let prefetch = Prefetch::from_file(&mut Cursor::new(fixture.build())).unwrap();
+
+  let correlations = prefetch.correlate_files(&Mft);
+
+  assert_eq!(correlations.len(), 2);
+  assert_eq!(correlations[0].path.as_deref(), Some("\\VOLUME{01}\\A.DLL"));
+  assert_eq!((correlations[0].mft_entry, correlations[0].mft_sequence), (0x10, 1));
+  assert!(!correlations[0].renamed);
+  assert_eq!(correlations[1].path.as_deref(), Some("\\VOLUME{01}\\C.DLL"));
+  assert_eq!(correlations[1].resolved_name.as_deref(), Some("RENAMED.DLL"));
+  assert!(correlations[1].renamed);
+}
@@ -0,0 +1,40 @@
+//! Detection of files loaded from network shares
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::Prefetch;
+
+use common::Fixture;
+
+fn network_executions(files : &[&str]) -> Vec<String>
+{
+  let buffer = Fixture::new(0x1a).files(files).build();
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+
+  prefetch.network_executions().iter().map(|path| path.to_string()).collect()
+}
+
+#[test]
+fn unc_path()
+{
+  let files = ["\\VOLUME{01d0000000000000-12345678}\\WINDOWS\\SYSTEM32\\NTDLL.DLL", "\\\\SERVER\\SHARE\\TOOL.EXE"];
+
+  assert_eq!(network_executions(&files), vec!["\\\\SERVER\\SHARE\\TOOL.EXE"]);
+}
+
+#[test]
+fn mup_device_path()
+{
+  let files = ["\\Device\\Mup\\server\\share\\tool.exe", "\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\KERNEL32.DLL"];
+
+  assert_eq!(network_executions(&files), vec!["\\Device\\Mup\\server\\share\\tool.exe"]);
+}
+
+#[test]
+fn local_paths()
+{
+  let files = ["\\VOLUME{01d0000000000000-12345678}\\WINDOWS\\NOTEPAD.EXE", "\\DEVICE\\HARDDISKVOLUME2\\MUP\\FILE.DLL"];
+
+  assert!(network_executions(&files).is_empty());
+}
@@ -0,0 +1,38 @@
+//! Heuristic path scan of ReadyBoot traces
+use std::io::Cursor;
+
+use tap_plugin_prefetch::readyboot::ReadyBootTrace;
+
+fn utf16(text : &str) -> Vec<u8>
+{
+  text.encode_utf16().chain([0]).flat_map(|unit| unit.to_le_bytes()).collect()
+}
+
+/// Trace with binary records around the strings, one of them not aligned on two bytes
+fn trace() -> Vec<u8>
+{
+  let mut data = b"\x1a\x00\x00\x00BOOT\x01\x02".to_vec();
+  data.extend(utf16("\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\NTOSKRNL.EXE"));
+  data.extend([0xff, 0x13, 0x37]);
+  data.extend(utf16("\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\HAL.DLL"));
+  data.extend(utf16("\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\NTOSKRNL.EXE"));
+  data.extend(utf16("\\A"));
+  data.extend(utf16("BOOTMGR"));
+  data.extend(utf16("\\ROOT\\TAB\tNAME"));
+  data
+}
+
+#[test]
+fn candidate_paths()
+{
+  let trace = ReadyBootTrace::scan_paths(&mut Cursor::new(trace())).unwrap();
+
+  assert_eq!(trace.candidate_paths, ["\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\NTOSKRNL.EXE",
+                                     "\\DEVICE\\HARDDISKVOLUME2\\WINDOWS\\SYSTEM32\\HAL.DLL"]);
+}
+
+#[test]
+fn empty_trace()
+{
+  assert!(ReadyBootTrace::scan_paths(&mut Cursor::new(Vec::new())).unwrap().candidate_paths.is_empty());
+}
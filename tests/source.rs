@@ -0,0 +1,61 @@
+//! Batch parsing of the prefetch files of a directory or of a zip archive
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use tap_plugin_prefetch::source::{scan_prefetch_source, DirectorySource};
+
+use common::Fixture;
+
+/// Empty directory of the system temporary directory, removed first if a previous run left it
+fn directory(name : &str) -> PathBuf
+{
+  let directory = std::env::temp_dir().join(format!("tap-plugin-prefetch-source-{}-{}", name, std::process::id()));
+  let _ = fs::remove_dir_all(&directory);
+  fs::create_dir_all(&directory).unwrap();
+  directory
+}
+
+#[test]
+fn directory_source()
+{
+  let directory = directory("directory");
+  fs::write(directory.join("TEST.EXE-DEADBEEF.pf"), Fixture::new(0x1a).run_count(3).build()).unwrap();
+  fs::write(directory.join("BROKEN.EXE-DEADBEEF.PF"), b"not a prefetch").unwrap();
+  fs::write(directory.join("notes.txt"), b"").unwrap();
+
+  let results = scan_prefetch_source(&mut DirectorySource::new(&directory)).unwrap();
+
+  let names : Vec<PathBuf> = results.iter().map(|(name, _)| PathBuf::from(name)).collect();
+  assert_eq!(names, vec![directory.join("BROKEN.EXE-DEADBEEF.PF"), directory.join("TEST.EXE-DEADBEEF.pf")]);
+  assert!(results[0].1.is_err());
+  assert_eq!(results[1].1.as_ref().unwrap().run_count(), 3);
+  fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_source()
+{
+  use std::io::{Cursor, Write};
+
+  use tap_plugin_prefetch::MAX_REASONABLE_PREFETCH_SIZE;
+  use tap_plugin_prefetch::source::ZipSource;
+
+  let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+  let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+  archive.start_file("Prefetch/TEST.EXE-DEADBEEF.pf", options).unwrap();
+  archive.write_all(&Fixture::new(0x1e).run_count(5).build()).unwrap();
+  archive.start_file("Prefetch/Layout.ini", options).unwrap();
+  archive.write_all(b"[OptimalLayoutFile]").unwrap();
+  archive.start_file("Prefetch/HUGE.EXE-DEADBEEF.pf", options).unwrap();
+  archive.write_all(&vec![0u8; MAX_REASONABLE_PREFETCH_SIZE as usize + 1]).unwrap();
+  let data = archive.finish().unwrap().into_inner();
+
+  let results = scan_prefetch_source(&mut ZipSource::new(Cursor::new(data)).unwrap()).unwrap();
+
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].0, "Prefetch/TEST.EXE-DEADBEEF.pf");
+  assert_eq!(results[0].1.as_ref().unwrap().run_count(), 5);
+}
@@ -0,0 +1,54 @@
+//! Search of the Prefetch folders of a mounted image
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use tap_plugin_prefetch::list_prefetch_tree;
+
+use common::Fixture;
+
+/// Empty directory of the system temporary directory, removed first if a previous run left it
+fn image_root(name : &str) -> PathBuf
+{
+  let root = std::env::temp_dir().join(format!("tap-plugin-prefetch-{}-{}", name, std::process::id()));
+  let _ = fs::remove_dir_all(&root);
+  fs::create_dir_all(&root).unwrap();
+  root
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_prefetch_folder_is_skipped()
+{
+  use std::os::unix::fs::PermissionsExt;
+
+  let root = image_root("unreadable");
+  let prefetch = root.join("Windows").join("Prefetch");
+  fs::create_dir_all(&prefetch).unwrap();
+  fs::write(prefetch.join("TEST.EXE-DEADBEEF.pf"), Fixture::new(0x1a).build()).unwrap();
+  let locked = root.join("Backup").join("Prefetch");
+  fs::create_dir_all(&locked).unwrap();
+  fs::write(locked.join("OLD.EXE-DEADBEEF.pf"), Fixture::new(0x1a).build()).unwrap();
+  fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+  let paths = list_prefetch_tree(&root);
+
+  fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+  let paths = paths.unwrap();
+  assert!(paths.contains(&prefetch.join("TEST.EXE-DEADBEEF.pf")));
+  fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn prefetch_folders_are_found_case_insensitively()
+{
+  let root = image_root("case");
+  let prefetch = root.join("WINDOWS").join("prefetch");
+  fs::create_dir_all(&prefetch).unwrap();
+  fs::write(prefetch.join("TEST.EXE-DEADBEEF.PF"), Fixture::new(0x1a).build()).unwrap();
+  fs::write(prefetch.join("notes.txt"), b"").unwrap();
+
+  assert_eq!(list_prefetch_tree(&root).unwrap(), vec![prefetch.join("TEST.EXE-DEADBEEF.PF")]);
+  fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,134 @@
+//! Parsing of every volume entry with its device path and directories
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::Prefetch;
+
+use common::Fixture;
+
+fn parse(fixture : Fixture) -> Prefetch
+{
+  Prefetch::from_file(&mut Cursor::new(fixture.build())).unwrap()
+}
+
+#[test]
+fn single_volume()
+{
+  let prefetch = parse(Fixture::new(0x1a));
+
+  assert_eq!(prefetch.volumes.len(), 1);
+  assert_eq!(prefetch.volume().unwrap().volume_serial_number(), 0x1234_5678);
+  assert!(prefetch.warnings.is_empty());
+}
+
+fn check_extra_volumes(version : u32)
+{
+  let fixture = Fixture::new(version).extra_volume("\\DEVICE\\HARDDISKVOLUME3", 0xcafe, &["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"])
+                                     .extra_volume("\\DEVICE\\MUP", 0xbeef, &["\\DEVICE\\MUP\\SERVER", "\\DEVICE\\MUP\\SERVER\\SHARE"]);
+  let prefetch = parse(fixture);
+
+  assert_eq!(prefetch.volumes.len(), 3);
+  assert_eq!(prefetch.volumes[1].device_path(), "\\DEVICE\\HARDDISKVOLUME3");
+  assert_eq!(prefetch.volumes[1].volume_serial_number(), 0xcafe);
+  assert_eq!(prefetch.volumes[1].directories(), ["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"]);
+  assert_eq!(prefetch.volumes[2].directories().len(), 2);
+  assert_eq!(prefetch.directories().count(), 3);
+  assert_eq!(prefetch.output().volume_count, 3);
+  assert_eq!(prefetch.summary().volume_count, 3);
+  assert!(prefetch.warnings.is_empty());
+}
+
+#[test]
+fn xp_extra_volumes()
+{
+  check_extra_volumes(0x11);
+}
+
+#[test]
+fn windows8_extra_volumes()
+{
+  check_extra_volumes(0x1a);
+}
+
+#[test]
+fn windows10_extra_volumes()
+{
+  check_extra_volumes(0x1e);
+}
+
+#[test]
+fn directories_are_tagged_with_their_volume()
+{
+  let fixture = Fixture::new(0x1a).files(&["\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\A.EXE", "\\DEVICE\\MUP\\SERVER\\B.DLL"])
+                                  .extra_volume("\\DEVICE\\HARDDISKVOLUME3", 0xcafe, &["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"])
+                                  .extra_volume("\\DEVICE\\MUP", 0xbeef, &["\\DEVICE\\MUP\\SERVER"]);
+  let prefetch = parse(fixture);
+
+  let tagged : Vec<(u32, &str)> = prefetch.volume_directories().map(|(volume, directory)| (volume.volume_serial_number(), directory)).collect();
+  assert_eq!(tagged, vec![(0xcafe, "\\DEVICE\\HARDDISKVOLUME3\\TOOLS"), (0xbeef, "\\DEVICE\\MUP\\SERVER")]);
+  assert_eq!(prefetch.volume_index("\\device\\harddiskvolume3\\tools\\a.exe"), Some(1));
+  assert_eq!(prefetch.volume_index(&prefetch.files[1]), Some(2));
+  assert_eq!(prefetch.volume_index("\\DEVICE\\HARDDISKVOLUME30\\A.EXE"), None);
+}
+
+fn check_directories_after_metrics(version : u32)
+{
+  let fixture = Fixture::new(version).files(&["\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\A.EXE", "\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\B.DLL"])
+                                     .metric(0, 0x0001_0000_0000_0010)
+                                     .metric(1, 0x0001_0000_0000_0020)
+                                     .extra_volume("\\DEVICE\\HARDDISKVOLUME3", 0xcafe, &["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"]);
+  let prefetch = parse(fixture);
+
+  assert_eq!(prefetch.metrics().len(), 2);
+  assert_eq!(prefetch.volumes[1].directories(), ["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"]);
+  assert!(prefetch.warnings.is_empty());
+}
+
+#[test]
+fn vista_directories_after_metrics()
+{
+  check_directories_after_metrics(0x17);
+}
+
+#[test]
+fn windows8_directories_after_metrics()
+{
+  check_directories_after_metrics(0x1a);
+}
+
+#[test]
+fn windows10_directories_after_metrics()
+{
+  check_directories_after_metrics(0x1e);
+}
+
+fn check_padded_sections(version : u32)
+{
+  let fixture = Fixture::new(version).files(&["\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\A.EXE", "\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\B.DLL"])
+                                     .metric(1, 0x0001_0000_0000_0020)
+                                     .extra_volume("\\DEVICE\\HARDDISKVOLUME3", 0xcafe, &["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"])
+                                     .extra_volume("\\DEVICE\\MUP", 0xbeef, &["\\DEVICE\\MUP\\SERVER"])
+                                     .padding(0x18);
+  let prefetch = parse(fixture);
+
+  assert_eq!(prefetch.files, ["\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\A.EXE", "\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\B.DLL"]);
+  assert_eq!(prefetch.file_references[1].mft_entry, 0x20);
+  assert_eq!(prefetch.volumes[1].device_path(), "\\DEVICE\\HARDDISKVOLUME3");
+  assert_eq!(prefetch.volumes[1].directories(), ["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"]);
+  assert_eq!(prefetch.volumes[2].device_path(), "\\DEVICE\\MUP");
+  assert_eq!(prefetch.volumes[2].directories(), ["\\DEVICE\\MUP\\SERVER"]);
+  assert!(prefetch.warnings.is_empty());
+}
+
+#[test]
+fn windows8_padded_sections()
+{
+  check_padded_sections(0x1a);
+}
+
+#[test]
+fn windows10_padded_sections()
+{
+  check_padded_sections(0x1e);
+}
@@ -0,0 +1,52 @@
+//! Severity and field of the parse warnings
+mod common;
+
+use std::io::Cursor;
+
+use tap_plugin_prefetch::{Prefetch, PrefetchParseOptions, PrefetchVersion};
+use tap_plugin_prefetch::warning::Severity;
+
+use common::Fixture;
+
+#[test]
+fn implausible_run_count()
+{
+  let buffer = Fixture::new(0x1a).run_count(1_000_000).build();
+  let prefetch = Prefetch::from_file(&mut Cursor::new(buffer)).unwrap();
+
+  assert_eq!(prefetch.warnings.len(), 1);
+  assert_eq!(prefetch.warnings[0].severity, Severity::Warning);
+  assert_eq!(prefetch.warnings[0].field, "run_count");
+  assert_eq!(prefetch.warnings_at_least(Severity::Error).count(), 0);
+}
+
+#[test]
+fn run_count_warning_offset()
+{
+  for (version, offset) in [(0x11, 0x90), (0x17, 0x98), (0x1a, 0xd0), (0x1e, 0xd0)]
+  {
+    let prefetch = Prefetch::from_bytes(&Fixture::new(version).run_count(1_000_000).build()).unwrap();
+
+    assert_eq!(prefetch.warnings[0].offset, Some(offset));
+  }
+}
+
+#[test]
+fn assumed_version_is_info_only()
+{
+  let buffer = Fixture::new(0x1a).build();
+  let options = PrefetchParseOptions{ assume_version : Some(PrefetchVersion::Windows8), strict : true, ..Default::default() };
+  let prefetch = Prefetch::from_file_with_options(&mut Cursor::new(buffer), &options).unwrap();
+
+  assert_eq!(prefetch.warnings.len(), 1);
+  assert_eq!(prefetch.warnings[0].severity, Severity::Info);
+  assert_eq!(prefetch.warnings[0].offset, Some(0));
+  assert_eq!(prefetch.warnings_at_least(Severity::Warning).count(), 0);
+}
+
+#[test]
+fn parse_severity()
+{
+  assert_eq!("Warning".parse::<Severity>().unwrap(), Severity::Warning);
+  assert!("fatal".parse::<Severity>().is_err());
+}
@@ -0,0 +1,69 @@
+//! Round trip of a parsed prefetch through `Prefetch::to_bytes`
+mod common;
+
+use tap_plugin_prefetch::Prefetch;
+
+use common::{Fixture, FILETIME};
+
+/// One hour in FILETIME units
+const HOUR : u64 = 36_000_000_000;
+
+fn check_round_trip(fixture : Fixture)
+{
+  let prefetch = Prefetch::from_bytes(&fixture.build()).unwrap();
+  let data = prefetch.to_bytes().unwrap();
+  let written = Prefetch::from_bytes(&data).unwrap();
+
+  assert_eq!(written.header.version(), prefetch.header.version());
+  assert_eq!(written.executable_name(), prefetch.executable_name());
+  assert_eq!(written.header.hash(), prefetch.header.hash());
+  assert_eq!(written.run_count(), prefetch.run_count());
+  assert_eq!(written.file_information.last_execution_times(), prefetch.file_information.last_execution_times());
+  assert_eq!(written.files, prefetch.files);
+  assert_eq!(written.file_references, prefetch.file_references);
+  assert_eq!(written.metrics().len(), prefetch.metrics().len());
+  assert_eq!(written.volumes.len(), prefetch.volumes.len());
+  for (written, volume) in written.volumes.iter().zip(&prefetch.volumes)
+  {
+    assert_eq!(written.device_path(), volume.device_path());
+    assert_eq!(written.volume_serial_number(), volume.volume_serial_number());
+    assert_eq!(written.volume_creation_date(), volume.volume_creation_date());
+    assert_eq!(written.directories(), volume.directories());
+  }
+  assert!(written.warnings.is_empty());
+  assert_eq!(written.to_bytes().unwrap(), data);
+}
+
+fn fixture(version : u32) -> Fixture
+{
+  Fixture::new(version).executable_name("ROUND.EXE")
+                       .run_count(9)
+                       .files(&["\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\ROUND.EXE", "\\DEVICE\\HARDDISKVOLUME3\\TOOLS\\ROUND.DLL"])
+                       .extra_volume("\\DEVICE\\HARDDISKVOLUME3", 0xcafe, &["\\DEVICE\\HARDDISKVOLUME3\\TOOLS"])
+}
+
+#[test]
+fn xp_round_trip()
+{
+  check_round_trip(fixture(0x11));
+}
+
+#[test]
+fn vista_round_trip()
+{
+  check_round_trip(fixture(0x17).metric(1, 0x0001_0000_0000_0010));
+}
+
+#[test]
+fn windows8_round_trip()
+{
+  check_round_trip(fixture(0x1a).metric(1, 0x0001_0000_0000_0010).metric(0, 0x0002_0000_0000_0020)
+                                .previous_execution_times(&[FILETIME - HOUR, FILETIME - 2 * HOUR]));
+}
+
+#[test]
+fn windows10_round_trip()
+{
+  check_round_trip(fixture(0x1e).metric(0, 0x0002_0000_0000_0020)
+                                .previous_execution_times(&[FILETIME - HOUR]));
+}